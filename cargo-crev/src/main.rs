@@ -122,6 +122,7 @@ fn review_crate(args: &opts::Crate, trust: TrustOrDistrust) -> Result<()> {
             digest_type: proof::default_digest_type(),
             revision: "".into(),
             revision_type: proof::default_revision_type(),
+            alt_digests: Default::default(),
         })
         .review(trust.to_default_score())
         .build()
@@ -20,6 +20,33 @@ pub fn now() -> chrono::DateTime<chrono::offset::FixedOffset> {
     date.with_timezone(&date.offset())
 }
 
+/// Source of the current date, eg. for the dates of new proofs
+///
+/// Lets tests sign with reproducible dates.
+pub trait Clock {
+    fn now(&self) -> chrono::DateTime<chrono::offset::FixedOffset>;
+}
+
+/// The system clock, see `now`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> chrono::DateTime<chrono::offset::FixedOffset> {
+        now()
+    }
+}
+
+/// A clock always returning the same date
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub chrono::DateTime<chrono::offset::FixedOffset>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> chrono::DateTime<chrono::offset::FixedOffset> {
+        self.0
+    }
+}
+
 pub fn blake2sum(bytes: &[u8]) -> Vec<u8> {
     let mut hasher = blake2::Blake2b::new();
     hasher.input(bytes);
@@ -7,7 +7,7 @@ use base64;
 use chrono::{self, offset::FixedOffset, prelude::*};
 use hex::{self, FromHex, FromHexError};
 use serde_yaml;
-use std::{fmt, io};
+use std::{collections::BTreeMap, fmt, io};
 
 // {{{ Serde serialization
 pub trait MyTryFromBytes: Sized {
@@ -91,24 +91,163 @@ impl MyTryFromBytes for Vec<u8> {
         Ok(Vec::from(slice))
     }
 }
+
+/// Like `from_base64`, for optional fields
+pub fn from_base64_opt<'d, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'d>,
+    T: MyTryFromBytes,
+{
+    use self::serde::de::Error;
+    Option::<String>::deserialize(deserializer)?
+        .map(|string| {
+            let bytes = base64::decode_config(&string, base64::URL_SAFE)
+                .map_err(|err| Error::custom(err.to_string()))?;
+            T::try_from(&bytes)
+                .map_err(|err| Error::custom(format!("{}", &err as &dyn (::std::error::Error))))
+        })
+        .transpose()
+}
+
+/// Like `as_base64`, for optional fields
+pub fn as_base64_opt<T, S>(key: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<[u8]>,
+    S: serde::Serializer,
+{
+    match key {
+        Some(key) => {
+            serializer.serialize_some(&base64::encode_config(key.as_ref(), base64::URL_SAFE))
+        }
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Like `from_base64`, for the values of a map
+pub fn from_base64_map<'d, D>(deserializer: D) -> Result<BTreeMap<String, Vec<u8>>, D::Error>
+where
+    D: serde::Deserializer<'d>,
+{
+    use self::serde::de::Error;
+    BTreeMap::<String, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(key, string)| {
+            let bytes = base64::decode_config(&string, base64::URL_SAFE)
+                .map_err(|err| Error::custom(err.to_string()))?;
+            Ok((key, bytes))
+        })
+        .collect()
+}
+
+/// Like `as_base64`, for the values of a map
+pub fn as_base64_map<S>(map: &BTreeMap<String, Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use self::serde::ser::SerializeMap;
+    let mut ser_map = serializer.serialize_map(Some(map.len()))?;
+    for (key, value) in map {
+        ser_map.serialize_entry(key, &base64::encode_config(value, base64::URL_SAFE))?;
+    }
+    ser_map.end()
+}
+
+/// Like `from_rfc3339_fixed`, for optional fields
+pub fn from_rfc3339_fixed_opt<'d, D>(
+    deserializer: D,
+) -> Result<Option<chrono::DateTime<FixedOffset>>, D::Error>
+where
+    D: serde::Deserializer<'d>,
+{
+    use self::serde::de::Error;
+    Option::<String>::deserialize(deserializer)?
+        .map(|string| {
+            DateTime::<FixedOffset>::parse_from_rfc3339(&string)
+                .map_err(|err| Error::custom(err.to_string()))
+        })
+        .transpose()
+}
+
+/// Like `as_rfc3339_fixed`, for optional fields
+pub fn as_rfc3339_fixed_opt<S>(
+    key: &Option<chrono::DateTime<FixedOffset>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match key {
+        Some(key) => serializer.serialize_some(&key.to_rfc3339()),
+        None => serializer.serialize_none(),
+    }
+}
 // }}}
 
+/// Forwards YAML written by `serde_yaml` to a `fmt::Write`, dropping the `---` header
+struct HeaderlessYamlWriter<'a, 'b> {
+    out: &'a mut fmt::Formatter<'b>,
+    /// Part of the header not seen yet
+    header_left: &'static [u8],
+    /// Incomplete UTF-8 sequence at the end of the last write
+    partial: Vec<u8>,
+    ends_with_newline: bool,
+}
+
+impl<'a, 'b> io::Write for HeaderlessYamlWriter<'a, 'b> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        while !self.header_left.is_empty() && !rest.is_empty() {
+            if rest[0] != self.header_left[0] {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "missing YAML header",
+                ));
+            }
+            rest = &rest[1..];
+            self.header_left = &self.header_left[1..];
+        }
+        self.partial.extend_from_slice(rest);
+        let valid_len = match std::str::from_utf8(&self.partial) {
+            Ok(s) => s.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        };
+        if valid_len > 0 {
+            let valid = std::str::from_utf8(&self.partial[..valid_len]).expect("checked above");
+            self.out
+                .write_str(valid)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            self.ends_with_newline = valid.ends_with('\n');
+            self.partial.drain(..valid_len);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 /// Write out a value as YAML without a `---` prefix
 ///
-/// This is how a lot of stuff in `Crev` is serialized
+/// This is how a lot of stuff in `Crev` is serialized. The document is
+/// streamed into `f`, and always ends with a newline.
 pub fn write_as_headerless_yaml<T: self::serde::Serialize>(
     t: &T,
     f: &mut fmt::Formatter<'_>,
 ) -> fmt::Result {
-    // TODO: Don't serialize to string, and instead serialize to writer
-    let yaml_document = serde_yaml::to_string(t).map_err(|_| fmt::Error)?;
-    let mut lines = yaml_document.lines();
-    let dropped_header = lines.next();
-    assert_eq!(dropped_header, Some("---"));
-
-    for line in lines {
-        f.write_str(&line)?;
-        f.write_str("\n")?;
+    let mut writer = HeaderlessYamlWriter {
+        out: f,
+        header_left: b"---\n",
+        partial: vec![],
+        ends_with_newline: false,
+    };
+    serde_yaml::to_writer(&mut writer, t).map_err(|_| fmt::Error)?;
+    if !writer.header_left.is_empty() || !writer.partial.is_empty() {
+        return Err(fmt::Error);
+    }
+    if !writer.ends_with_newline {
+        writer.out.write_str("\n")?;
     }
     Ok(())
 }
@@ -0,0 +1,75 @@
+//! Streaming `write_as_headerless_yaml` vs serializing to a `String` first
+//!
+//! Run with `cargo +nightly bench --features nightly`.
+
+#![feature(test)]
+
+extern crate test;
+
+use crev_data::{id::OwnId, proof};
+use std::path::PathBuf;
+use test::Bencher;
+
+fn big_review() -> proof::review::Code {
+    let id = OwnId::generate("https://example.com/proofs.git".into());
+    proof::review::CodeBuilder::default()
+        .from(id.id.to_owned())
+        .project(proof::ProjectInfo {
+            id: None,
+            source: "https://crates.io".into(),
+            name: "foo".into(),
+            version: "0.1.0".into(),
+            revision: "foobar".into(),
+            revision_type: "git".into(),
+            digest: vec![0; 32],
+            digest_type: "blake2b".into(),
+            alt_digests: Default::default(),
+        })
+        .comment("some comment ".repeat(500))
+        .files(
+            (0..1000)
+                .map(|i| proof::review::code::File {
+                    path: PathBuf::from(format!("src/file-{}.rs", i)),
+                    digest: vec![1; 32],
+                    digest_type: "sha256".into(),
+                })
+                .collect(),
+        )
+        .build()
+        .expect("valid review")
+}
+
+/// The approach used before streaming: whole document, then strip the header
+fn to_string_and_strip(review: &proof::review::Code) -> String {
+    let document = serde_yaml::to_string(review).expect("serializable");
+    let mut res = String::new();
+    for line in document.lines().skip(1) {
+        res += line;
+        res += "\n";
+    }
+    res
+}
+
+#[bench]
+fn streaming(b: &mut Bencher) {
+    let review = big_review();
+    b.iter(|| review.to_string());
+}
+
+#[bench]
+fn serialize_to_string_first(b: &mut Bencher) {
+    let review = big_review();
+    b.iter(|| to_string_and_strip(&review));
+}
+
+#[bench]
+fn streaming_digest(b: &mut Bencher) {
+    let content = proof::Content::from(big_review());
+    b.iter(|| content.digest_streaming(None).expect("serializable"));
+}
+
+#[bench]
+fn serialize_to_string_first_digest(b: &mut Bencher) {
+    let review = big_review();
+    b.iter(|| crev_common::blake2sum(to_string_and_strip(&review).as_bytes()));
+}
@@ -1,3 +1,4 @@
+use crate::Result;
 use std::fmt;
 
 pub struct Digest(Vec<u8>);
@@ -18,6 +19,36 @@ impl Digest {
     }
 }
 
+/// Valid lengths (in bytes) of digests of a given `digest_type`
+///
+/// `None` if the type is unknown to this version. `blake2b` digests
+/// are allowed both full (64) and truncated to 256bits (32), as
+/// created by `Digest::from_vec`.
+pub fn valid_lengths(digest_type: &str) -> Option<&'static [usize]> {
+    match digest_type {
+        "blake2b" => Some(&[32, 64]),
+        "sha256" => Some(&[32]),
+        _ => None,
+    }
+}
+
+/// Check that `digest` has a valid length for its `digest_type`
+///
+/// Digests of unknown types are accepted as they are.
+pub fn check_length(digest: &[u8], digest_type: &str) -> Result<()> {
+    if let Some(lengths) = valid_lengths(digest_type) {
+        if !lengths.contains(&digest.len()) {
+            bail!(
+                "Wrong {} digest length: {} (expected one of {:?})",
+                digest_type,
+                digest.len(),
+                lengths
+            );
+        }
+    }
+    Ok(())
+}
+
 impl fmt::Display for Digest {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(&base64::encode_config(&self.0, base64::URL_SAFE))
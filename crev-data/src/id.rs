@@ -25,11 +25,15 @@ impl fmt::Display for IdType {
 ///
 /// Right now it's only native CrevID, but in future at least GPG
 /// should be supported.
+///
+/// Equality (and hashing) is by the key only.
 #[derive(Clone, Debug, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "id-type")]
 pub enum Id {
     #[serde(rename = "crev")]
     Crev {
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
         #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
         id: Vec<u8>,
     },
@@ -42,17 +46,49 @@ impl Id {
         Ok(Id::Crev { id: bytes })
     }
 
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::from(self)
+    }
+
     pub fn verify_signature(&self, content: &[u8], sig_str: &str) -> Result<()> {
+        self.verify_signature_with(content, sig_str, &Ed25519Verifier)
+    }
+
+    /// Like `verify_signature`, but using `backend` to do the verification
+    pub fn verify_signature_with(
+        &self,
+        content: &[u8],
+        sig_str: &str,
+        backend: &dyn SignatureVerifier,
+    ) -> Result<()> {
         match self {
             Id::Crev { id } => {
-                let pubkey = ed25519_dalek::PublicKey::from_bytes(&id)?;
-
                 let sig_bytes = base64::decode_config(sig_str, base64::URL_SAFE)?;
-                let signature = ed25519_dalek::Signature::from_bytes(&sig_bytes)?;
-
-                pubkey.verify::<blake2::Blake2b>(content, &signature)?;
+                backend.verify(id, content, &sig_bytes)
             }
         }
+    }
+}
+
+/// Backend verifying the signatures of proofs
+///
+/// Lets integrators plug in eg. hardware tokens or other crypto libraries.
+/// `Ed25519Verifier` is the built-in one.
+pub trait SignatureVerifier {
+    /// Fail unless `signature` of `message` is valid for `pubkey`
+    fn verify(&self, pubkey: &[u8], message: &[u8], signature: &[u8]) -> Result<()>;
+}
+
+/// Built-in `SignatureVerifier`, using `ed25519_dalek`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ed25519Verifier;
+
+impl SignatureVerifier for Ed25519Verifier {
+    fn verify(&self, pubkey: &[u8], message: &[u8], signature: &[u8]) -> Result<()> {
+        let pubkey = ed25519_dalek::PublicKey::from_bytes(pubkey)?;
+        let signature = ed25519_dalek::Signature::from_bytes(signature)?;
+
+        pubkey.verify::<blake2::Blake2b>(message, &signature)?;
 
         Ok(())
     }
@@ -65,6 +101,91 @@ impl fmt::Display for Id {
         }
     }
 }
+
+/// Length of a `Fingerprint` in bytes
+pub const FINGERPRINT_LEN: usize = 16;
+
+/// Short, fixed-length digest identifying an `Id`
+///
+/// Displayed and parsed as hex.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fingerprint(Vec<u8>);
+
+impl Fingerprint {
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<'a> From<&'a Id> for Fingerprint {
+    fn from(id: &Id) -> Self {
+        match id {
+            Id::Crev { id } => {
+                let mut digest = crev_common::blake2sum(id);
+                digest.truncate(FINGERPRINT_LEN);
+                Fingerprint(digest)
+            }
+        }
+    }
+}
+
+impl<'a> From<&'a PubId> for Fingerprint {
+    fn from(id: &PubId) -> Self {
+        Fingerprint::from(&id.id)
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&hex::encode(&self.0))
+    }
+}
+
+impl serde::Serialize for Fingerprint {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Fingerprint {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        use serde::de::Error;
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(|e| D::Error::custom(format!("{}", e)))
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Fingerprint {
+    fn schema_name() -> String {
+        "Fingerprint".into()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
+impl std::str::FromStr for Fingerprint {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let bytes = hex::decode(s)?;
+        if bytes.len() != FINGERPRINT_LEN {
+            bail!(
+                "Wrong fingerprint length: {} (expected {})",
+                bytes.len(),
+                FINGERPRINT_LEN
+            );
+        }
+        Ok(Fingerprint(bytes))
+    }
+}
 /*
 impl<T: Borrow<id::PubId>> From<T> for IdAndUrl {
     fn from(id: T) -> Self {
@@ -83,6 +204,7 @@ impl<T: Borrow<id::PubId>> From<T> for IdAndUrl {
 */
 
 #[derive(Clone, Debug, Builder, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PubId {
     #[serde(flatten)]
     pub id: Id,
@@ -104,11 +226,28 @@ impl PubId {
         }
     }
 
+    /// Do both refer to the same key, no matter their urls
+    pub fn same_key(&self, other: &PubId) -> bool {
+        self.id == other.id
+    }
+
     pub fn new_crevid_from_base64(s: &str) -> Result<Self> {
-        let v = base64::decode_config(s, base64::URL_SAFE)?;
+        Self::from_base64(s, None)
+    }
+
+    /// Build from a URL-safe base64 encoded public key
+    pub fn from_base64(key_b64: &str, url: Option<String>) -> Result<Self> {
+        let v = base64::decode_config(key_b64, base64::URL_SAFE)?;
+        if v.len() != ed25519_dalek::PUBLIC_KEY_LENGTH {
+            bail!(
+                "Wrong public key length: {} (expected {})",
+                v.len(),
+                ed25519_dalek::PUBLIC_KEY_LENGTH
+            );
+        }
         Ok(PubId {
             id: Id::Crev { id: v },
-            url: None,
+            url: url.map(Url::new),
         })
     }
     pub fn set_git_url(&mut self, url: String) {
@@ -180,8 +319,8 @@ impl AsRef<PubId> for OwnId {
 }
 
 impl OwnId {
-    pub fn new(url: String, sec_key: Vec<u8>) -> Result<Self> {
-        let sec_key = SecretKey::from_bytes(&sec_key)?;
+    pub fn new(url: String, sec_key: &[u8]) -> Result<Self> {
+        let sec_key = SecretKey::from_bytes(sec_key)?;
         let calculated_pub_key: PublicKey = PublicKey::from_secret::<blake2::Blake2b>(&sec_key);
 
         Ok(Self {
@@ -214,6 +353,39 @@ impl OwnId {
     }
     */
 
+    /// Recover an id from a 24 word (BIP39, English) mnemonic phrase
+    ///
+    /// The phrase encodes the secret key directly, so the same phrase
+    /// always yields the same id.
+    #[cfg(feature = "mnemonic")]
+    pub fn from_mnemonic(phrase: &str, url: String) -> Result<Self> {
+        let mnemonic = bip39::Mnemonic::from_phrase(phrase, bip39::Language::English)?;
+        if mnemonic.entropy().len() != ed25519_dalek::SECRET_KEY_LENGTH {
+            bail!(
+                "Wrong mnemonic length: {} words (expected 24)",
+                phrase.split_whitespace().count()
+            );
+        }
+        Self::new(url, mnemonic.entropy())
+    }
+
+    /// Mnemonic phrase of the secret key, to back up the id
+    #[cfg(feature = "mnemonic")]
+    pub fn to_mnemonic(&self) -> String {
+        bip39::Mnemonic::from_entropy(self.keypair.secret.as_bytes(), bip39::Language::English)
+            .expect("secret key length is a valid entropy length")
+            .phrase()
+            .to_owned()
+    }
+
+    /// Generate a fresh id with a dummy url
+    ///
+    /// Only for tests.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn generate_for_test() -> Self {
+        Self::generate("https://example.com/crev-test.git".into())
+    }
+
     pub fn generate(url: String) -> Self {
         let mut csprng: OsRng = OsRng::new().unwrap();
         let keypair = ed25519_dalek::Keypair::generate::<blake2::Blake2b, _>(&mut csprng);
@@ -1,15 +1,44 @@
-use crate::Result;
-use std::fmt;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{convert::TryFrom, fmt, str::FromStr};
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
-#[serde(rename_all = "lowercase")]
+/// Level of trust, distrust, thoroughness, etc.
+///
+/// Levels unknown to this version (eg. added in some future version
+/// of `crev`) are preserved as `Unknown`, with their name, so they
+/// round-trip.
+///
+/// # Ordering
+///
+/// `Unknown` levels order below all the known ones, `None` included.
+/// A level this version can't interpret must not count as any amount
+/// of trust (or distrust), so checks like `level > Level::None` or
+/// `level >= Level::Low` are false for them. Among themselves, unknown
+/// levels order by name.
+///
+/// # Compatibility
+///
+/// Since `Unknown` owns its name, `Level` is not `Copy` anymore. This
+/// is a breaking change: code copying levels out of references (eg.
+/// `let level = score.trust;` from a `&Score`) has to `clone()` them.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Level {
+    Unknown(String),
     None,
     Low,
     Medium,
     High,
 }
 
+/// All the known levels, with their serialized names
+///
+/// This is the only place that needs to change when adding a level.
+const KNOWN_LEVELS: &[(Level, &str)] = &[
+    (Level::None, "none"),
+    (Level::Low, "low"),
+    (Level::Medium, "medium"),
+    (Level::High, "high"),
+];
+
 impl Default for Level {
     fn default() -> Self {
         Level::Medium
@@ -18,25 +47,127 @@ impl Default for Level {
 
 impl fmt::Display for Level {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use self::Level::*;
-        f.write_str(match self {
-            None => "none",
-            Low => "low",
-            Medium => "medium",
-            High => "high",
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Level {
+    type Err = std::string::ParseError;
+
+    fn from_str(s: &str) -> Result<Level, Self::Err> {
+        Ok(KNOWN_LEVELS
+            .iter()
+            .find(|(_, name)| *name == s)
+            .map(|(level, _)| level.clone())
+            .unwrap_or_else(|| Level::Unknown(s.to_owned())))
+    }
+}
+
+/// Numeric level outside of the known range of levels
+#[derive(Fail, Debug)]
+#[fail(display = "Level out of range: {}", _0)]
+pub struct OutOfRange(pub u8);
+
+impl TryFrom<u8> for Level {
+    type Error = OutOfRange;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
+        Ok(match v {
+            0 => Level::None,
+            1 => Level::Low,
+            2 => Level::Medium,
+            3 => Level::High,
+            _ => return Err(OutOfRange(v)),
         })
     }
 }
 
+impl From<Level> for u8 {
+    /// `Unknown` levels convert like `None`, as they carry no known weight
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Unknown(_) | Level::None => 0,
+            Level::Low => 1,
+            Level::Medium => 2,
+            Level::High => 3,
+        }
+    }
+}
+
+impl Serialize for Level {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Level {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(s.parse().unwrap_or_else(|e| match e {}))
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Level {
+    fn schema_name() -> String {
+        "Level".into()
+    }
+
+    // Serialized as a plain string, see `Serialize`
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
 impl Level {
-    #[allow(unused)]
-    fn from_str(s: &str) -> Result<Level> {
-        Ok(match s {
-            "none" => Level::None,
-            "low" => Level::Low,
-            "medium" => Level::Medium,
-            "high" => Level::High,
-            _ => bail!("Unknown level: {}", s),
-        })
+    pub fn as_str(&self) -> &str {
+        match self {
+            Level::Unknown(s) => s.as_str(),
+            known => KNOWN_LEVELS
+                .iter()
+                .find(|(level, _)| level == known)
+                .map(|(_, name)| *name)
+                .expect("all known levels are listed in KNOWN_LEVELS"),
+        }
+    }
+
+    pub fn is_known(&self) -> bool {
+        if let Level::Unknown(_) = self {
+            return false;
+        }
+        true
+    }
+
+    /// The lower of the two, eg. the level of a chain of trust
+    pub fn min(self, other: Level) -> Level {
+        std::cmp::min(self, other)
+    }
+
+    /// The higher of the two
+    pub fn max(self, other: Level) -> Level {
+        std::cmp::max(self, other)
+    }
+
+    /// One level lower, saturating at `None`
+    ///
+    /// `Unknown` levels stay as they are.
+    pub fn step_down(self) -> Level {
+        self.step_by(-1)
+    }
+
+    /// One level higher, saturating at `High`
+    ///
+    /// `Unknown` levels stay as they are.
+    pub fn step_up(self) -> Level {
+        self.step_by(1)
+    }
+
+    fn step_by(self, step: isize) -> Level {
+        let i = match KNOWN_LEVELS.iter().position(|(level, _)| *level == self) {
+            Some(i) => i as isize,
+            None => return self,
+        };
+        let i = (i + step).max(0).min(KNOWN_LEVELS.len() as isize - 1);
+        KNOWN_LEVELS[i as usize].0.clone()
     }
 }
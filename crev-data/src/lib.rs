@@ -22,7 +22,7 @@ pub mod util;
 
 pub use crate::{
     digest::Digest,
-    id::{Id, PubId},
+    id::{Fingerprint, Id, PubId},
     level::Level,
     proof::review::Score,
     url::Url,
@@ -30,7 +30,68 @@ pub use crate::{
 
 /// Current API version
 pub fn current_version() -> i64 {
-    -99999 // still WIP; 0 == "release 1.0.0"
+    -99998 // still WIP; 0 == "release 1.0.0"
+}
+
+/// All versions this build can read, oldest first
+pub fn supported_versions() -> &'static [i64] {
+    &[-99999, -99998]
+}
+
+/// Features of the proof format that older versions lack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    /// Signed bytes are prefixed with a domain separation string
+    SigningDomainPrefix,
+    /// Trust proofs can have an expiration date
+    TrustExpiration,
+    /// Proofs can be co-signed by other ids
+    MultiSignature,
+}
+
+impl Feature {
+    /// First version with this feature
+    pub fn since_version(self) -> i64 {
+        match self {
+            Feature::SigningDomainPrefix => -99998,
+            Feature::TrustExpiration => -99998,
+            Feature::MultiSignature => -99998,
+        }
+    }
+}
+
+/// Does `version` (eg. of a proof, or of an id) have `feature`
+pub fn version_supports(version: i64, feature: Feature) -> bool {
+    version >= feature.since_version()
+}
+
+/// Fail unless `version` has `feature`
+pub fn ensure_version_supports(version: i64, feature: Feature) -> Result<()> {
+    if !version_supports(version, feature) {
+        bail!(
+            "{:?} requires version {} or newer, not {}",
+            feature,
+            feature.since_version(),
+            version
+        );
+    }
+    Ok(())
+}
+
+/// JSON schemas of all the proof contents emitted by this version
+///
+/// A JSON object with a schema for each content kind (see
+/// `Content::kind_str`), generated from the actual types.
+#[cfg(feature = "schema")]
+pub fn schema_json() -> String {
+    let schemas: std::collections::BTreeMap<_, _> = vec![
+        ("trust", schemars::schema_for!(proof::Trust)),
+        ("code", schemars::schema_for!(proof::review::Code)),
+        ("project", schemars::schema_for!(proof::review::Project)),
+    ]
+    .into_iter()
+    .collect();
+    serde_json::to_string_pretty(&schemas).expect("schemas always serialize")
 }
 
 #[cfg(test)]
@@ -0,0 +1,91 @@
+use crate::{proof::Proof, Result};
+use crev_common;
+use serde_yaml;
+use std::{collections::BTreeMap, fmt, io, mem};
+
+const BEGIN_ANNOTATIONS: &str = "-----BEGIN CREV ANNOTATIONS-----";
+const END_ANNOTATIONS: &str = "-----END CREV ANNOTATIONS-----";
+
+/// A `Proof` with local, unsigned annotations
+///
+/// Annotations (eg. "imported from peer X") are serialized in a separate
+/// block following the proof, so the signed proof block stays intact.
+#[derive(Debug, Clone)]
+pub struct AnnotatedProof {
+    pub proof: Proof,
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl From<Proof> for AnnotatedProof {
+    fn from(proof: Proof) -> Self {
+        AnnotatedProof {
+            proof,
+            annotations: Default::default(),
+        }
+    }
+}
+
+impl fmt::Display for AnnotatedProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.proof.fmt(f)?;
+        if !self.annotations.is_empty() {
+            f.write_str(BEGIN_ANNOTATIONS)?;
+            f.write_str("\n")?;
+            crev_common::serde::write_as_headerless_yaml(&self.annotations, f)?;
+            f.write_str(END_ANNOTATIONS)?;
+            f.write_str("\n")?;
+        }
+        Ok(())
+    }
+}
+
+impl AnnotatedProof {
+    /// Parse proofs, each optionally followed by its annotations block
+    pub fn parse(reader: impl io::BufRead) -> Result<Vec<Self>> {
+        let mut res: Vec<AnnotatedProof> = vec![];
+        let mut proofs_text = String::new();
+        let mut annotations_text: Option<String> = None;
+
+        for line in reader.lines() {
+            let line = line?;
+            match annotations_text.take() {
+                Some(mut text) => {
+                    if line.trim() == END_ANNOTATIONS {
+                        res.last_mut()
+                            .ok_or_else(|| format_err!("Annotations without a proof"))?
+                            .annotations = serde_yaml::from_str(&text)?;
+                    } else {
+                        text += &line;
+                        text += "\n";
+                        annotations_text = Some(text);
+                    }
+                }
+                None => {
+                    if line.trim() == BEGIN_ANNOTATIONS {
+                        let text = mem::replace(&mut proofs_text, String::new());
+                        res.extend(Proof::parse(text.as_bytes())?.into_iter().map(Self::from));
+                        annotations_text = Some(String::new());
+                    } else {
+                        proofs_text += &line;
+                        proofs_text += "\n";
+                    }
+                }
+            }
+        }
+
+        if annotations_text.is_some() {
+            bail!("Unexpected EOF while parsing annotations");
+        }
+        res.extend(
+            Proof::parse(proofs_text.as_bytes())?
+                .into_iter()
+                .map(Self::from),
+        );
+
+        Ok(res)
+    }
+
+    pub fn verify(&self) -> Result<()> {
+        self.proof.verify()
+    }
+}
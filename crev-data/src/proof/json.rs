@@ -0,0 +1,144 @@
+//! JSON Lines export of proofs, for machine consumers
+//!
+//! Each line contains the parsed content for convenience, but also
+//! the original `body` and `signature`, so exported proofs can be
+//! imported back and verified.
+
+use crate::{
+    proof::{CoSignature, Content, Proof, ProofType, Serialized},
+    Id, Result,
+};
+use base64;
+use serde_json;
+use std::{collections::BTreeMap, io};
+
+#[derive(Serialize, Deserialize)]
+struct JsonProof {
+    #[serde(rename = "type")]
+    kind: String,
+    author: String,
+    digest: String,
+    content: serde_json::Value,
+    body: String,
+    signature: String,
+    #[serde(rename = "sig-alg", skip_serializing_if = "Option::is_none", default)]
+    signature_algorithm: Option<String>,
+    #[serde(
+        rename = "co-signatures",
+        skip_serializing_if = "Vec::is_empty",
+        default
+    )]
+    co_signatures: Vec<JsonCoSignature>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonCoSignature {
+    id: String,
+    signature: String,
+}
+
+/// Sort keys of all the objects in `value`
+///
+/// `serde_json::Map` keeps insertion order if its `preserve_order`
+/// feature gets enabled anywhere in the dependency graph, so
+/// don't rely on it being sorted already.
+fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let sorted: BTreeMap<_, _> = map
+                .into_iter()
+                .map(|(key, value)| (key, sort_keys(value)))
+                .collect();
+            serde_json::Value::Object(sorted.into_iter().collect())
+        }
+        serde_json::Value::Array(values) => {
+            serde_json::Value::Array(values.into_iter().map(sort_keys).collect())
+        }
+        other => other,
+    }
+}
+
+fn content_to_json(content: &Content) -> Result<serde_json::Value> {
+    Ok(sort_keys(match content {
+        Content::Trust(trust) => serde_json::to_value(trust)?,
+        Content::Code(review) => serde_json::to_value(review)?,
+        Content::Project(review) => serde_json::to_value(review)?,
+    }))
+}
+
+impl Content {
+    /// Compact JSON of the content, with all object keys sorted
+    ///
+    /// Byte-stable for a given content.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&content_to_json(self)?)?)
+    }
+}
+
+fn proof_type_from_kind_str(s: &str) -> Result<ProofType> {
+    Ok(match s {
+        "trust" => ProofType::Trust,
+        "code" => ProofType::Code,
+        "project" => ProofType::Project,
+        _ => bail!("Unknown proof type: {}", s),
+    })
+}
+
+/// Write `proofs` as JSON Lines: one JSON object per proof
+pub fn export_jsonl(proofs: &[Proof], mut writer: impl io::Write) -> Result<()> {
+    for proof in proofs {
+        let json = JsonProof {
+            kind: proof.content.kind_str().into(),
+            author: proof.content.author_id().to_string(),
+            digest: base64::encode_config(&proof.digest, base64::URL_SAFE),
+            content: content_to_json(&proof.content)?,
+            body: proof.body.clone(),
+            signature: proof.signature.clone(),
+            signature_algorithm: proof.signature_algorithm.clone(),
+            co_signatures: proof
+                .co_signatures
+                .iter()
+                .map(|co_signature| JsonCoSignature {
+                    id: co_signature.id.to_string(),
+                    signature: co_signature.signature.clone(),
+                })
+                .collect(),
+        };
+        serde_json::to_writer(&mut writer, &json)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Read back proofs written by `export_jsonl`
+///
+/// Content is always re-parsed from the `body`, never from the `content`.
+pub fn import_jsonl(reader: impl io::BufRead) -> Result<Vec<Proof>> {
+    let mut proofs = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let json: JsonProof = serde_json::from_str(&line)?;
+        let mut proof = Serialized {
+            body: json.body,
+            signature: json.signature,
+            type_: proof_type_from_kind_str(&json.kind)?,
+        }
+        .to_parsed()?;
+        proof.signature_algorithm = json.signature_algorithm;
+        proof.co_signatures = json
+            .co_signatures
+            .into_iter()
+            .map(|co_signature| {
+                Ok(CoSignature {
+                    id: Id::crevid_from_str(&co_signature.id)?,
+                    signature: co_signature.signature,
+                })
+            })
+            .collect::<Result<_>>()?;
+        proofs.push(proof);
+    }
+    Ok(proofs)
+}
@@ -1,21 +1,62 @@
 //! Some common stuff for both Review and Trust Proofs
+//!
+//! # Renaming fields
+//!
+//! When a field of a proof content is renamed, the old name is kept
+//! as a `#[serde(alias = "...")]`, so older proofs still parse, while
+//! drafts and newly signed proofs use the new name.
+//!
+//! Aliases never change the signed bytes of existing proofs: signatures
+//! are always verified over the original `body` exactly as it was read
+//! (see `Proof::verify`), and never over a re-serialized `Content`.
 
 use crate::level::Level;
 use base64;
 use chrono::{self, prelude::*};
 use crev_common;
-use std::{default, fmt, fs, io, mem, path::Path};
+use std::{
+    collections::HashSet,
+    default, fmt, fs, io, mem,
+    path::{Path, PathBuf},
+};
 
+pub mod annotated;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod policy;
 pub mod project_info;
 pub mod review;
 pub mod revision;
+pub mod timestamp;
+pub mod transparency;
 pub mod trust;
 
-pub use self::{project_info::*, revision::*, trust::*};
+pub use self::{
+    annotated::*, policy::*, project_info::*, revision::*, timestamp::*, transparency::*, trust::*,
+};
 
 use crate::Result;
 
+/// Domain separation prefix of signed bytes
+///
+/// Prevents a signature over a proof body from being replayed
+/// in other protocols that sign similar bytes.
+const SIGNING_DOMAIN_PREFIX: &str = "crev-proof-v1\n";
+
+/// Bytes actually signed for a proof `body` of a given `version`
+///
+/// Eg. to sign or verify a body with a key held outside of `crev`.
+pub fn signed_bytes(body: &str, version: i64) -> Vec<u8> {
+    if !crate::version_supports(version, crate::Feature::SigningDomainPrefix) {
+        return body.as_bytes().to_vec();
+    }
+    let mut bytes = SIGNING_DOMAIN_PREFIX.as_bytes().to_vec();
+    bytes.extend_from_slice(body.as_bytes());
+    bytes
+}
+
 pub trait ContentCommon {
+    fn version(&self) -> i64;
     fn date(&self) -> &chrono::DateTime<FixedOffset>;
     fn author(&self) -> &crate::PubId;
 
@@ -32,7 +73,7 @@ pub trait ContentCommon {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum ProofType {
     Code,
     Project,
@@ -63,11 +104,95 @@ impl ProofType {
     }
 }
 
+/// Proof file contains a byte sequence that is not valid UTF-8
+#[derive(Fail, Debug)]
+#[fail(display = "Invalid UTF-8 in line {} at byte offset {}", line, offset)]
+pub struct InvalidUtf8 {
+    /// Line number, starting at 1
+    pub line: usize,
+    /// Offset of the first invalid byte from the start of the input
+    pub offset: usize,
+}
+
+/// Size limits enforced when parsing proofs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Maximum length of a proof body, in bytes
+    pub max_body_len: usize,
+    /// Maximum length of the signature section of a proof, in bytes
+    pub max_signature_len: usize,
+    /// Maximum length of a single line, line ending excluded, in bytes
+    ///
+    /// Checked while reading, so a huge line without any newline
+    /// is rejected before being buffered whole.
+    pub max_line_len: usize,
+    /// Maximum length of all the proof bodies in one input, in bytes
+    ///
+    /// Eg. for hosting, to cap a whole file of proofs each under
+    /// `max_body_len`. Unlimited by default.
+    pub max_total_body_bytes: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        ParseLimits {
+            max_body_len: 16_000,
+            max_signature_len: 2000,
+            max_line_len: 16_000,
+            max_total_body_bytes: usize::max_value(),
+        }
+    }
+}
+
+/// Fail if a body of `len` bytes exceeds the default `ParseLimits`
+fn check_body_len(len: usize) -> Result<()> {
+    let max_len = ParseLimits::default().max_body_len;
+    if len > max_len {
+        bail!(
+            "Proof body too long: {} bytes, the limit is {}; trim at least {} bytes (eg. of the comment)",
+            len,
+            max_len,
+            len - max_len
+        );
+    }
+    Ok(())
+}
+
+/// What to hide in `Content::redacted`
+#[derive(Debug, Clone, Default)]
+pub struct RedactOptions {
+    /// Remove the url of the author
+    pub hide_author_url: bool,
+    /// Truncate comments longer than that many characters
+    pub max_comment_len: Option<usize>,
+}
+
+/// Subject of a new proof, see `Content::template`
+///
+/// Only the fields relevant to the requested proof type are used.
+#[derive(Debug, Clone)]
+pub struct TemplateInput {
+    /// Author of the proof
+    pub from: crate::PubId,
+    /// Reviewed project, required for code and project reviews
+    pub project: Option<ProjectInfo>,
+    /// Reviewed files, for code reviews
+    pub files: Vec<review::code::File>,
+    /// Trusted ids, for trust proofs
+    pub ids: Vec<crate::PubId>,
+}
+
+/// Marks a comment truncated by `Content::redacted`
+pub const REDACTED_MARKER: &str = "[redacted]";
+
 /// Serialized Proof
 ///
 /// A signed proof containing some signed `Content`
+///
+/// The content is not parsed, so it can be a content this version
+/// doesn't understand; see `Serialized::verify_signature_only`.
 #[derive(Debug, Clone)]
-pub(crate) struct Serialized {
+pub struct Serialized {
     /// Serialized content
     pub body: String,
     /// Signature over the body
@@ -129,15 +254,263 @@ impl Content {
             ProofType::Trust => Content::Trust(TrustDraft::parse(&s)?.into()),
         })
     }
+
+    /// A new proof content about `target`, to start editing a draft from
+    ///
+    /// Dated now, with default levels and an empty comment.
+    pub fn template(type_: ProofType, target: TemplateInput) -> Result<Content> {
+        Self::template_with_clock(type_, target, &crev_common::SystemClock)
+    }
+
+    /// Like `template`, but dated by `clock`
+    pub fn template_with_clock(
+        type_: ProofType,
+        target: TemplateInput,
+        clock: &dyn crev_common::Clock,
+    ) -> Result<Content> {
+        let TemplateInput {
+            from,
+            project,
+            files,
+            ids,
+        } = target;
+        let project =
+            || project.ok_or_else(|| format_err!("{:?} template without a project", type_));
+        Ok(match type_ {
+            ProofType::Code => Content::Code(
+                review::CodeBuilder::default()
+                    .date(clock.now())
+                    .from(from)
+                    .project(project()?)
+                    .files(files)
+                    .build()
+                    .map_err(|e| format_err!("{}", e))?,
+            ),
+            ProofType::Project => Content::Project(
+                review::ProjectBuilder::default()
+                    .date(clock.now())
+                    .from(from)
+                    .project(project()?)
+                    .build()
+                    .map_err(|e| format_err!("{}", e))?,
+            ),
+            ProofType::Trust => Content::Trust(
+                TrustBuilder::default()
+                    .date(clock.now())
+                    .from(from)
+                    .ids(ids)
+                    .build()
+                    .map_err(|e| format_err!("{}", e))?,
+            ),
+        })
+    }
+
+    /// Like `parse_draft`, but re-parsed from its canonical serialization
+    ///
+    /// Whitespace, field order, etc. of a hand-edited `draft` don't matter:
+    /// the content is exactly what reading back a signed proof would give.
+    pub fn from_canonical_draft(draft: &str, type_: ProofType) -> Result<Content> {
+        let content = Self::parse_draft(draft, type_)?;
+        Self::parse(&content.to_string(), type_)
+    }
+
+    /// Sign the content with `id`
+    ///
+    /// The canonical serialization (`Display`) of the content is always
+    /// what gets signed, no matter how the draft it came from was formatted.
+    ///
+    /// Fails if the result couldn't be parsed back, see `fits_parse_limits`.
     pub fn sign_by(&self, id: &crate::id::OwnId) -> Result<Proof> {
+        self.ensure_features_supported()?;
         let body = self.to_string();
-        let signature = id.sign(&body.as_bytes());
-        Ok(Proof {
+        check_body_len(body.len())?;
+        Ok(self.sign_serialized(id, body))
+    }
+
+    /// Like `sign_by`, but dated by `clock` instead of the date of the content
+    ///
+    /// With a `crev_common::FixedClock`, signing is reproducible.
+    pub fn sign_by_with_clock(
+        &self,
+        id: &crate::id::OwnId,
+        clock: &dyn crev_common::Clock,
+    ) -> Result<Proof> {
+        self.sign_by_at(id, clock.now())
+    }
+
+    /// Fail if the serialized content exceeds the default `ParseLimits`
+    ///
+    /// A proof like that could be signed, but never parsed back.
+    pub fn fits_parse_limits(&self) -> Result<()> {
+        check_body_len(self.serialized_len())
+    }
+
+    /// Fail if the content uses features its version doesn't have
+    ///
+    /// A proof like that could be signed, but never parsed back.
+    fn ensure_features_supported(&self) -> Result<()> {
+        match self {
+            Content::Trust(trust) => trust.ensure_features_supported(),
+            Content::Code(_) | Content::Project(_) => Ok(()),
+        }
+    }
+
+    /// Like `sign_by`, but with `date` instead of the date of the content
+    ///
+    /// Eg. to re-sign migrated content, while keeping its original date.
+    pub fn sign_by_at(
+        &self,
+        id: &crate::id::OwnId,
+        date: chrono::DateTime<FixedOffset>,
+    ) -> Result<Proof> {
+        use self::Content::*;
+        let mut content = self.clone();
+        match content {
+            Trust(ref mut trust) => trust.date = date,
+            Code(ref mut review) => *review.date_mut() = date,
+            Project(ref mut review) => *review.date_mut() = date,
+        }
+        content.sign_by(id)
+    }
+
+    /// Bytes to sign with the key of the author, eg. on an offline machine
+    ///
+    /// Offline signing goes like this: create the content with a fixed
+    /// date (eg. `TrustBuilder::date`), move `signing_bytes` to where the
+    /// key is and sign them there, then `attach_signature` to get the
+    /// proof. Once online, it can be timestamped with
+    /// `AnnotatedProof::attach_timestamp`.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        signed_bytes(&self.to_string(), self.version())
+    }
+
+    /// The proof of this content, with a `signature` of `signing_bytes`
+    ///
+    /// Fails if the signature is not valid for the author.
+    pub fn attach_signature(&self, signature: &[u8]) -> Result<Proof> {
+        self.ensure_features_supported()?;
+        let body = self.to_string();
+        check_body_len(body.len())?;
+        let proof = Proof {
+            digest: crev_common::blake2sum(&body.as_bytes()),
+            body,
+            signature: base64::encode_config(signature, base64::URL_SAFE),
+            co_signatures: vec![],
+            signature_algorithm: None,
+            content: self.clone(),
+        };
+        proof.verify()?;
+        Ok(proof)
+    }
+
+    /// Like `sign_by`, but record `producer` (eg. "cargo-crev 0.5.0") in the proof
+    pub fn sign_by_with_producer(&self, id: &crate::id::OwnId, producer: &str) -> Result<Proof> {
+        let mut content = self.clone();
+        content.set_producer(Some(producer.to_owned()));
+        content.sign_by(id)
+    }
+
+    pub fn producer(&self) -> Option<&str> {
+        use self::Content::*;
+        match self {
+            Trust(trust) => trust.producer.as_ref(),
+            Code(review) => review.producer.as_ref(),
+            Project(review) => review.producer.as_ref(),
+        }
+        .map(String::as_str)
+    }
+
+    pub fn set_producer(&mut self, producer: Option<String>) {
+        use self::Content::*;
+        match self {
+            Trust(trust) => trust.producer = producer,
+            Code(review) => review.producer = producer,
+            Project(review) => review.producer = producer,
+        }
+    }
+
+    /// Sign in memory only, eg. to show the user what will be committed
+    ///
+    /// Nothing is persisted. Signing is deterministic, so the result
+    /// is identical to a `sign_by` of the same content with the same id.
+    pub fn preview_signed(&self, id: &crate::id::OwnId) -> Proof {
+        self.sign_serialized(id, self.to_string())
+    }
+
+    /// Sign `body`, which must be the serialization of this content
+    fn sign_serialized(&self, id: &crate::id::OwnId, body: String) -> Proof {
+        let signature = id.sign(&signed_bytes(&body, self.version()));
+        Proof {
             digest: crev_common::blake2sum(&body.as_bytes()),
             body: body,
             signature: base64::encode_config(&signature, base64::URL_SAFE),
+            co_signatures: vec![],
+            signature_algorithm: None,
             content: self.clone(),
-        })
+        }
+    }
+
+    /// The final proof block, exactly as it would be written out
+    pub fn preview_block(&self, id: &crate::id::OwnId) -> String {
+        self.preview_signed(id).to_string()
+    }
+
+    /// Length in bytes of the serialized body, as signed by `sign_by`
+    pub fn serialized_len(&self) -> usize {
+        struct Counter(usize);
+
+        impl fmt::Write for Counter {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0 += s.len();
+                Ok(())
+            }
+        }
+
+        let mut counter = Counter(0);
+        fmt::write(&mut counter, format_args!("{}", self))
+            .expect("a Display implementation returned an error unexpectedly");
+        counter.0
+    }
+
+    /// Digest of the serialized body, without holding it all in memory
+    ///
+    /// Equal to the `digest` of the proof `sign_by` creates. The body is
+    /// hashed as it is serialized, and also written to `tee`, if given.
+    /// Signing itself still needs the whole body, see `preview_signed`.
+    pub fn digest_streaming(&self, tee: Option<&mut dyn io::Write>) -> Result<Vec<u8>> {
+        use blake2::{digest::FixedOutput, Digest};
+
+        struct Hashing<'a> {
+            hasher: blake2::Blake2b,
+            tee: Option<&'a mut dyn io::Write>,
+            tee_error: Option<io::Error>,
+        }
+
+        impl<'a> fmt::Write for Hashing<'a> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.hasher.input(s.as_bytes());
+                if let Some(ref mut tee) = self.tee {
+                    if let Err(e) = tee.write_all(s.as_bytes()) {
+                        self.tee_error = Some(e);
+                        return Err(fmt::Error);
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        let mut hashing = Hashing {
+            hasher: blake2::Blake2b::new(),
+            tee,
+            tee_error: None,
+        };
+        if fmt::write(&mut hashing, format_args!("{}", self)).is_err() {
+            return Err(match hashing.tee_error {
+                Some(e) => e.into(),
+                None => format_err!("Failed to serialize the proof content"),
+            });
+        }
+        Ok(hashing.hasher.fixed_result().to_vec())
     }
 
     pub fn proof_type(&self) -> ProofType {
@@ -149,6 +522,167 @@ impl Content {
         }
     }
 
+    pub fn version(&self) -> i64 {
+        use self::Content::*;
+        match self {
+            Trust(trust) => trust.version(),
+            Code(review) => review.version(),
+            Project(review) => review.version(),
+        }
+    }
+
+    /// Check that the content is complete and ready to be signed
+    ///
+    /// Drafts might parse fine, but still be missing what they are about.
+    pub fn validate(&self) -> Result<()> {
+        use self::Content::*;
+        match self {
+            Trust(trust) => {
+                if trust.ids.is_empty() && trust.fingerprints.is_empty() {
+                    bail!("Trust proof without any ids");
+                }
+            }
+            Code(review) => {
+                if review.files.is_empty() {
+                    bail!("Code review without any files");
+                }
+                review.project.validate()?;
+                if let Some(ref reference) = review.reference {
+                    reference.validate_http()?;
+                }
+            }
+            Project(review) => {
+                review.project.validate()?;
+                if let Some(ref reference) = review.reference {
+                    reference.validate_http()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that digests in the content are well-formed
+    ///
+    /// Only checks the length for the declared digest type, so it doesn't
+    /// need the source, and catches malformed proofs early.
+    pub fn verify_content_digest(&self) -> Result<()> {
+        use self::Content::*;
+        match self {
+            Trust(_trust) => {}
+            Code(review) => {
+                review.project.verify_digest()?;
+                for file in &review.files {
+                    crate::digest::check_length(&file.digest, &file.digest_type)
+                        .map_err(|e| format_err!("File {}: {}", file.path.display(), e))?;
+                }
+            }
+            Project(review) => review.project.verify_digest()?,
+        }
+        Ok(())
+    }
+
+    /// Like `validate`, but just a quick query, eg. to enable a "sign" button
+    pub fn is_complete(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    /// Is `other` about the same subject, by the same author
+    ///
+    /// Date, comment and levels are ignored, so this can be used to find
+    /// reviews/trust proofs superseded by a newer one.
+    pub fn same_subject(&self, other: &Content) -> bool {
+        use self::Content::*;
+        if self.author_id() != other.author_id() {
+            return false;
+        }
+        match (self, other) {
+            (Trust(a), Trust(b)) => {
+                let ids_a: HashSet<_> = a.ids.iter().map(|id| &id.id).collect();
+                let ids_b: HashSet<_> = b.ids.iter().map(|id| &id.id).collect();
+                let fingerprints_a: HashSet<_> = a.fingerprints.iter().collect();
+                let fingerprints_b: HashSet<_> = b.fingerprints.iter().collect();
+                ids_a == ids_b && fingerprints_a == fingerprints_b
+            }
+            (Code(a), Code(b)) => {
+                let files_a: HashSet<_> = a.files.iter().map(|f| (&f.path, &f.digest)).collect();
+                let files_b: HashSet<_> = b.files.iter().map(|f| (&f.path, &f.digest)).collect();
+                a.project.same_package(&b.project) && files_a == files_b
+            }
+            (Project(a), Project(b)) => a.project.same_package(&b.project),
+            _ => false,
+        }
+    }
+
+    /// Digest of the proof this one supersedes, if any
+    pub fn supersedes(&self) -> Option<&[u8]> {
+        use self::Content::*;
+        match self {
+            Trust(_trust) => None,
+            Code(review) => review.supersedes.as_ref().map(Vec::as_slice),
+            Project(review) => review.supersedes.as_ref().map(Vec::as_slice),
+        }
+    }
+
+    /// Digest of the reviewed source, `None` for trust proofs
+    ///
+    /// See `ProjectInfo::digests` for the digests of other types.
+    pub fn target_digest(&self) -> Option<&[u8]> {
+        use self::Content::*;
+        match self {
+            Trust(_trust) => None,
+            Code(review) => Some(review.project.digest.as_slice()),
+            Project(review) => Some(review.project.digest.as_slice()),
+        }
+    }
+
+    pub fn comment(&self) -> &str {
+        use self::Content::*;
+        match self {
+            Trust(trust) => trust.comment(),
+            Code(review) => review.comment(),
+            Project(review) => review.comment(),
+        }
+    }
+
+    /// A copy for public display, with some fields hidden
+    ///
+    /// The result no longer matches the signed body: it is only for display,
+    /// and can't be verified against the original signature. Truncated
+    /// comments end with `REDACTED_MARKER`.
+    pub fn redacted(&self, opts: &RedactOptions) -> Content {
+        use self::Content::*;
+        let mut content = self.clone();
+        if opts.hide_author_url {
+            match content {
+                Trust(ref mut trust) => trust.from.url = None,
+                Code(ref mut review) => review.from.url = None,
+                Project(ref mut review) => review.from.url = None,
+            }
+        }
+        if let Some(max_len) = opts.max_comment_len {
+            let comment = match content {
+                Trust(ref mut trust) => trust.comment_mut(),
+                Code(ref mut review) => review.comment_mut(),
+                Project(ref mut review) => review.comment_mut(),
+            };
+            if let Some((byte_offset, _)) = comment.char_indices().nth(max_len) {
+                comment.truncate(byte_offset);
+                comment.push_str(REDACTED_MARKER);
+            }
+        }
+        content
+    }
+
+    /// Stable name of the content kind, eg. for logging
+    pub fn kind_str(&self) -> &'static str {
+        use self::Content::*;
+        match self {
+            Trust(_trust) => "trust",
+            Code(_review) => "code",
+            Project(_review) => "project",
+        }
+    }
+
     pub fn date(&self) -> &chrono::DateTime<FixedOffset> {
         use self::Content::*;
         match self {
@@ -158,6 +692,19 @@ impl Content {
         }
     }
 
+    pub fn date_utc(&self) -> chrono::DateTime<Utc> {
+        self.date().with_timezone(&Utc)
+    }
+
+    pub fn author(&self) -> &crate::PubId {
+        use self::Content::*;
+        match self {
+            Trust(trust) => trust.author(),
+            Code(review) => review.author(),
+            Project(review) => review.author(),
+        }
+    }
+
     pub fn author_id(&self) -> crate::Id {
         use self::Content::*;
         match self {
@@ -191,10 +738,141 @@ impl Content {
 pub struct Proof {
     pub body: String,
     pub signature: String,
+    /// Additional signatures over the same body, by other ids
+    pub co_signatures: Vec<CoSignature>,
+    /// Algorithm of all the signatures, see `Proof::signature_algorithm`
+    ///
+    /// `None` for proofs that don't declare it, which all are ed25519.
+    pub signature_algorithm: Option<String>,
     pub digest: Vec<u8>,
     pub content: Content,
 }
 
+/// Size metrics of a proof, see `Proof::metrics`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofMetrics {
+    pub body_len: usize,
+    pub body_lines: usize,
+    /// Length of the (base64 encoded) signature
+    pub signature_len: usize,
+}
+
+/// Proofs already known to verify, see `Proof::verify_cached`
+///
+/// Keyed by a digest of the whole proof, signatures included: the
+/// proof `digest` covers only the body, so the same body with
+/// a different signature is not the same verified proof.
+#[derive(Debug, Clone, Default)]
+pub struct VerifyCache {
+    verified: HashSet<Vec<u8>>,
+}
+
+impl VerifyCache {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.verified.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.verified.is_empty()
+    }
+
+    fn key(proof: &Proof) -> Vec<u8> {
+        crev_common::blake2sum(proof.to_string().as_bytes())
+    }
+}
+
+/// Proofs are equal if their digests (so, the bodies) are
+impl PartialEq for Proof {
+    fn eq(&self, other: &Self) -> bool {
+        self.digest == other.digest
+    }
+}
+
+impl Eq for Proof {}
+
+/// Proofs are ordered by date, then by digest
+///
+/// This uses a different key than equality on purpose (eg. for
+/// time-ordered `BinaryHeap`s), but stays consistent with it: the date
+/// is a part of the body, so equal proofs always compare `Equal`.
+impl Ord for Proof {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.content.date_utc(), &self.digest).cmp(&(other.content.date_utc(), &other.digest))
+    }
+}
+
+impl PartialOrd for Proof {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A signature of a proof by someone other than its author
+///
+/// Serialized in the signature section, after the author's signature,
+/// as one `<id> <signature>` line each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoSignature {
+    pub id: crate::Id,
+    pub signature: String,
+}
+
+/// Prefix of the line declaring the signature algorithm, in the signature section
+const SIGNATURE_ALGORITHM_PREFIX: &str = "sig-alg:";
+
+/// Signatures in the signature section of a proof
+struct Signatures {
+    signature: String,
+    co_signatures: Vec<CoSignature>,
+    algorithm: Option<String>,
+}
+
+/// Split the signature section into author's signature and co-signatures
+///
+/// Whitespace within the author's signature is ignored. An optional
+/// `sig-alg: <algorithm>` line declares the algorithm of all of them.
+fn split_signatures(section: &str) -> Result<Signatures> {
+    let mut signature = String::new();
+    let mut co_signatures = vec![];
+    let mut algorithm = None;
+    for line in section.lines() {
+        let line = line.trim();
+        if line.starts_with(SIGNATURE_ALGORITHM_PREFIX) {
+            algorithm = Some(line[SIGNATURE_ALGORITHM_PREFIX.len()..].trim().to_owned());
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(id), Some(sig), None) => co_signatures.push(CoSignature {
+                id: crate::Id::crevid_from_str(id)?,
+                signature: sig.to_owned(),
+            }),
+            // the signature itself might be wrapped over many lines
+            _ => signature += line,
+        }
+    }
+    Ok(Signatures {
+        signature,
+        co_signatures,
+        algorithm,
+    })
+}
+
+/// Fail with `UnsupportedAlgorithm` unless signatures use `algorithm`
+fn check_signature_algorithm(algorithm: &str) -> Result<()> {
+    if algorithm != SIGNATURE_ALGORITHM {
+        return Err(UnsupportedAlgorithm {
+            algorithm: algorithm.to_owned(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
 impl fmt::Display for Serialized {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(self.type_.begin_block())?;
@@ -213,35 +891,112 @@ impl fmt::Display for Serialized {
 
 impl fmt::Display for Proof {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with_signature(f, self.signature())
+    }
+}
+
+impl Proof {
+    fn write_with_signature(&self, f: &mut dyn fmt::Write, signature: &str) -> fmt::Result {
         f.write_str(self.content.proof_type().begin_block())?;
         f.write_str("\n")?;
         f.write_str(&self.body)?;
         f.write_str(self.content.proof_type().begin_signature())?;
         f.write_str("\n")?;
-        f.write_str(&self.signature)?;
+        if let Some(ref algorithm) = self.signature_algorithm {
+            writeln!(f, "{} {}", SIGNATURE_ALGORITHM_PREFIX, algorithm)?;
+        }
+        f.write_str(signature)?;
         f.write_str("\n")?;
+        for co_signature in &self.co_signatures {
+            writeln!(f, "{} {}", co_signature.id, co_signature.signature)?;
+        }
         f.write_str(self.content.proof_type().end_block())?;
         f.write_str("\n")?;
 
         Ok(())
     }
+
+    /// Like `to_string`, but with the signature wrapped at `width` columns
+    ///
+    /// `None` writes the signature in a single line. Co-signatures are
+    /// always written one per line.
+    pub fn to_string_wrapped(&self, width: Option<usize>) -> String {
+        let signature = match width {
+            Some(width) if width > 0 => self
+                .signature()
+                .as_bytes()
+                .chunks(width)
+                .map(|chunk| std::str::from_utf8(chunk).expect("base64 is ascii"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => self.signature().to_owned(),
+        };
+        let mut res = String::new();
+        self.write_with_signature(&mut res, &signature)
+            .expect("writing to a String can't fail");
+        res
+    }
+
+    /// Like `to_string`, but with only the first `prefix_len` characters
+    /// of the signature, followed by an ellipsis
+    ///
+    /// For compact display only: the result can't be verified.
+    pub fn to_string_abbrev_sig(&self, prefix_len: usize) -> String {
+        let mut signature: String = self.signature().chars().take(prefix_len).collect();
+        signature.push('…');
+        let mut res = String::new();
+        self.write_with_signature(&mut res, &signature)
+            .expect("writing to a String can't fail");
+        res
+    }
 }
 
 impl Serialized {
     pub fn to_parsed(&self) -> Result<Proof> {
+        let Signatures {
+            signature,
+            co_signatures,
+            algorithm,
+        } = split_signatures(&self.signature)?;
+        let content = match self.type_ {
+            ProofType::Code => Content::Code(review::Code::parse(&self.body)?),
+            ProofType::Project => Content::Project(review::Project::parse(&self.body)?),
+            ProofType::Trust => Content::Trust(Trust::parse(&self.body)?),
+        };
+        if !co_signatures.is_empty() {
+            crate::ensure_version_supports(content.version(), crate::Feature::MultiSignature)?;
+        }
         Ok(Proof {
             body: self.body.clone(),
-            signature: self.signature.clone(),
+            signature,
+            co_signatures,
+            signature_algorithm: algorithm,
             digest: crev_common::blake2sum(&self.body.as_bytes()),
-            content: match self.type_ {
-                ProofType::Code => Content::Code(review::Code::parse(&self.body)?),
-                ProofType::Project => Content::Project(review::Project::parse(&self.body)?),
-                ProofType::Trust => Content::Trust(Trust::parse(&self.body)?),
-            },
+            content,
         })
     }
 
     pub fn parse(reader: impl io::BufRead) -> Result<Vec<Self>> {
+        Self::parse_with_limits(reader, ParseLimits::default())
+    }
+
+    pub fn parse_with_limits(reader: impl io::BufRead, limits: ParseLimits) -> Result<Vec<Self>> {
+        let mut proofs = vec![];
+        Self::parse_each(reader, limits, |proof| {
+            proofs.push(proof);
+            true
+        })?;
+        Ok(proofs)
+    }
+
+    /// Pass each proof to `f` as soon as it's read, until `f` returns `false`
+    ///
+    /// Nothing after the proof `f` stopped at is read.
+    pub fn parse_each(
+        mut reader: impl io::BufRead,
+        limits: ParseLimits,
+        mut f: impl FnMut(Self) -> bool,
+    ) -> Result<()> {
         #[derive(PartialEq, Eq)]
         enum Stage {
             None,
@@ -261,6 +1016,9 @@ impl Serialized {
             signature: String,
             type_: ProofType,
             proofs: Vec<Serialized>,
+            limits: ParseLimits,
+            /// Length of the bodies of the proofs already read
+            total_body_len: usize,
         }
 
         impl default::Default for State {
@@ -271,6 +1029,8 @@ impl Serialized {
                     signature: Default::default(),
                     type_: ProofType::Trust, // whatever
                     proofs: vec![],
+                    limits: Default::default(),
+                    total_body_len: 0,
                 }
             }
         }
@@ -301,13 +1061,21 @@ impl Serialized {
                             self.body += line;
                             self.body += "\n";
                         }
-                        if self.body.len() > 16_000 {
+                        if self.body.len() > self.limits.max_body_len {
                             bail!("Proof body too long");
                         }
+                        let total_len = self.total_body_len + self.body.len();
+                        if total_len > self.limits.max_total_body_bytes {
+                            bail!(
+                                "Proof bodies too long in total: over {} bytes",
+                                self.limits.max_total_body_bytes
+                            );
+                        }
                     }
                     Stage::Signature => {
                         if line.trim() == self.type_.end_block() {
                             self.stage = Stage::None;
+                            self.total_body_len += self.body.len();
                             self.proofs.push(Serialized {
                                 body: mem::replace(&mut self.body, String::new()),
                                 signature: mem::replace(&mut self.signature, String::new()),
@@ -317,7 +1085,7 @@ impl Serialized {
                             self.signature += line;
                             self.signature += "\n";
                         }
-                        if self.signature.len() > 2000 {
+                        if self.signature.len() > self.limits.max_signature_len {
                             bail!("Signature too long");
                         }
                     }
@@ -325,22 +1093,98 @@ impl Serialized {
                 Ok(())
             }
 
-            fn finish(self) -> Result<Vec<Serialized>> {
+            fn finish(self) -> Result<()> {
                 if self.stage != Stage::None {
                     bail!("Unexpected EOF while parsing");
                 }
-                Ok(self.proofs)
+                Ok(())
             }
         }
 
-        let mut state: State = Default::default();
+        let mut state = State {
+            limits,
+            ..Default::default()
+        };
 
-        for line in reader.lines() {
-            state.process_line(&line?)?;
+        use std::io::{BufRead, Read};
+
+        let mut buf = vec![];
+        let mut line_num = 0;
+        let mut offset = 0;
+        loop {
+            buf.clear();
+            // room for the line ending, to tell a too long line from a long enough one
+            let max_read = limits.max_line_len as u64 + 2;
+            let len = (&mut reader).take(max_read).read_until(b'\n', &mut buf)?;
+            if len == 0 {
+                break;
+            }
+            line_num += 1;
+            let has_newline = buf.last() == Some(&b'\n');
+            if !has_newline && len as u64 == max_read {
+                bail!("Line {} too long", line_num);
+            }
+            // strip the line ending just like `BufRead::lines` does:
+            // one `\n`, and then at most one `\r`
+            let mut line = &buf[..];
+            if has_newline {
+                line = &line[..line.len() - 1];
+                if line.last() == Some(&b'\r') {
+                    line = &line[..line.len() - 1];
+                }
+            }
+            if line.len() > limits.max_line_len {
+                bail!("Line {} too long", line_num);
+            }
+            let line = std::str::from_utf8(line).map_err(|e| InvalidUtf8 {
+                line: line_num,
+                offset: offset + e.valid_up_to(),
+            })?;
+            state.process_line(line)?;
+            offset += len;
+            for proof in state.proofs.drain(..) {
+                if !f(proof) {
+                    return Ok(());
+                }
+            }
         }
 
         state.finish()
     }
+
+    /// Verify the signature of the body with `pubkey`, without parsing
+    /// the content
+    ///
+    /// Only the `version` of the body is read (it determines the signed
+    /// bytes), so it works even for content this version doesn't understand.
+    pub fn verify_signature_only(&self, pubkey: &crate::PubId) -> Result<()> {
+        #[derive(Deserialize)]
+        struct VersionOnly {
+            version: i64,
+        }
+        let VersionOnly { version } = serde_yaml::from_str(&self.body)?;
+        let signatures = split_signatures(&self.signature)?;
+        if let Some(ref algorithm) = signatures.algorithm {
+            check_signature_algorithm(algorithm)?;
+        }
+
+        pubkey
+            .id
+            .verify_signature(&signed_bytes(&self.body, version), &signatures.signature)
+    }
+
+    /// Like `parse`, but fail on any proof not of `type_`
+    pub fn parse_expecting(reader: impl io::BufRead, type_: ProofType) -> Result<Vec<Self>> {
+        let proofs = Self::parse(reader)?;
+        if let Some(other) = proofs.iter().find(|proof| proof.type_ != type_) {
+            bail!(
+                "Expected only {:?} proofs, found a {:?} proof",
+                type_,
+                other.type_
+            );
+        }
+        Ok(proofs)
+    }
 }
 
 impl Proof {
@@ -349,9 +1193,187 @@ impl Proof {
         Self::parse(io::BufReader::new(file))
     }
 
+    /// Parse proofs piped to stdin, eg. in `cat *.crev | crev verify`
+    ///
+    /// Same as `parse` of stdin: an empty stdin gives no proofs (not an
+    /// error), and one closed in the middle of a proof fails with
+    /// "Unexpected EOF while parsing".
+    pub fn parse_stdin() -> Result<Vec<Self>> {
+        let stdin = io::stdin();
+        let locked = stdin.lock();
+        Self::parse(locked)
+    }
+
     pub fn parse(reader: impl io::BufRead) -> Result<Vec<Self>> {
+        Self::parse_with_limits(reader, ParseLimits::default())
+    }
+
+    /// Like `parse`, but with custom `limits`
+    pub fn parse_with_limits(reader: impl io::BufRead, limits: ParseLimits) -> Result<Vec<Self>> {
+        let mut v = vec![];
+        for serialized in Serialized::parse_with_limits(reader, limits)?.into_iter() {
+            v.push(serialized.to_parsed()?)
+        }
+        Ok(v)
+    }
+
+    /// A placeholder trust proof, eg. for tests and default values
+    ///
+    /// Authored by an all-zero key, dated at the Unix epoch, without any
+    /// ids, and with an empty signature: it parses back fine, but never
+    /// passes `verify`. Never publish it.
+    pub fn empty_trust() -> Self {
+        let content = Content::from(
+            TrustBuilder::default()
+                .date(FixedOffset::east(0).timestamp(0, 0))
+                .from(crate::PubId::new_from_pubkey(vec![0; 32]))
+                .ids(vec![])
+                .trust(Level::None)
+                .build()
+                .expect("all the required fields are set"),
+        );
+        let body = content.to_string();
+        Proof {
+            digest: crev_common::blake2sum(body.as_bytes()),
+            body,
+            signature: String::new(),
+            co_signatures: vec![],
+            signature_algorithm: None,
+            content,
+        }
+    }
+
+    /// Is this the placeholder `Proof::empty_trust`, ie. not signed at all
+    pub fn is_placeholder(&self) -> bool {
+        self.signature().is_empty()
+    }
+
+    /// Assemble a proof from a body and a detached signature
+    ///
+    /// The inverse of taking `body` and `signature()` of a proof apart.
+    pub fn from_detached(body: &str, signature_b64: &str, type_: ProofType) -> Result<Self> {
+        Serialized {
+            body: body.to_owned(),
+            signature: signature_b64.trim().to_owned(),
+            type_,
+        }
+        .to_parsed()
+    }
+
+    /// Parse all proof files (`*.crev`) in a tar archive
+    ///
+    /// Each proof is returned with the path of the archive entry it came from.
+    #[cfg(feature = "tar")]
+    pub fn parse_tar(reader: impl io::Read) -> Result<Vec<(PathBuf, Self)>> {
+        let mut archive = tar::Archive::new(reader);
+        let mut res = vec![];
+        for entry in archive.entries()? {
+            let entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let path = entry.path()?.into_owned();
+            if path.extension() != Some("crev".as_ref()) {
+                continue;
+            }
+            let proofs = Self::parse(io::BufReader::new(entry))
+                .map_err(|e| format_err!("Failed to parse {}: {}", path.display(), e))?;
+            res.extend(proofs.into_iter().map(|proof| (path.clone(), proof)));
+        }
+        Ok(res)
+    }
+
+    /// Parse proofs stored as git notes under `ref_name` (eg. `refs/notes/crev`)
+    ///
+    /// Each proof is returned with the id of the commit its note annotates.
+    /// Notes that don't contain any proof are skipped.
+    #[cfg(feature = "git")]
+    pub fn parse_git_notes(repo_path: &Path, ref_name: &str) -> Result<Vec<(String, Self)>> {
+        let repo = git2::Repository::open(repo_path)?;
+        let mut res = vec![];
+        for note in repo.notes(Some(ref_name))? {
+            let (_note_id, annotated_id) = note?;
+            let note = repo.find_note(Some(ref_name), annotated_id)?;
+            let message = match note.message() {
+                Some(message) => message,
+                None => continue,
+            };
+            if !message
+                .lines()
+                .any(|line| line.trim().starts_with("-----BEGIN "))
+            {
+                continue;
+            }
+            let proofs = Self::parse(message.as_bytes())
+                .map_err(|e| format_err!("Failed to parse note on {}: {}", annotated_id, e))?;
+            res.extend(
+                proofs
+                    .into_iter()
+                    .map(|proof| (annotated_id.to_string(), proof)),
+            );
+        }
+        Ok(res)
+    }
+
+    /// Parse proofs pasted into markdown code fences (eg. in issues)
+    ///
+    /// Text outside of fences, and fences without any proof, are ignored.
+    pub fn parse_markdown(s: &str) -> Result<Vec<Self>> {
+        let mut res = vec![];
+        let mut fence: Option<(&str, String)> = None;
+        for line in s.lines() {
+            let trimmed = line.trim();
+            match fence.take() {
+                None => {
+                    if trimmed.starts_with("```") {
+                        fence = Some(("```", String::new()));
+                    } else if trimmed.starts_with("~~~") {
+                        fence = Some(("~~~", String::new()));
+                    }
+                }
+                Some((marker, mut text)) => {
+                    if trimmed.starts_with(marker) {
+                        if text
+                            .lines()
+                            .any(|line| line.trim().starts_with("-----BEGIN "))
+                        {
+                            res.extend(Self::parse(text.as_bytes())?);
+                        }
+                    } else {
+                        text += line;
+                        text += "\n";
+                        fence = Some((marker, text));
+                    }
+                }
+            }
+        }
+        if fence.is_some() {
+            bail!("Unexpected EOF inside a code fence");
+        }
+        Ok(res)
+    }
+
+    /// Like `parse`, but return only proofs of `type_`
+    ///
+    /// Contents of other proofs are never parsed, so they are cheap to skip,
+    /// and don't even need to be valid.
+    pub fn parse_only(reader: impl io::BufRead, type_: ProofType) -> Result<Vec<Self>> {
+        let mut serialized = vec![];
+        Serialized::parse_each(reader, ParseLimits::default(), |proof| {
+            if proof.type_ == type_ {
+                serialized.push(proof);
+            }
+            true
+        })?;
+        serialized.iter().map(Serialized::to_parsed).collect()
+    }
+
+    /// Like `parse`, but fail on any proof not of `type_`
+    ///
+    /// Useful for files expected to be homogeneous, eg. trust-only seed files.
+    pub fn parse_expecting(reader: impl io::BufRead, type_: ProofType) -> Result<Vec<Self>> {
         let mut v = vec![];
-        for serialized in Serialized::parse(reader)?.into_iter() {
+        for serialized in Serialized::parse_expecting(reader, type_)?.into_iter() {
             v.push(serialized.to_parsed()?)
         }
         Ok(v)
@@ -368,12 +1390,298 @@ impl Proof {
         self.signature.trim()
     }
 
+    /// Algorithm of the signatures, `SIGNATURE_ALGORITHM` if not declared
+    pub fn signature_algorithm(&self) -> &str {
+        self.signature_algorithm
+            .as_ref()
+            .map(String::as_str)
+            .unwrap_or(SIGNATURE_ALGORITHM)
+    }
+
     pub fn verify(&self) -> Result<()> {
+        self.verify_with_backend(&crate::id::Ed25519Verifier)
+    }
+
+    /// Like `verify`, but with signatures checked by `backend`
+    ///
+    /// Fails with `UnsupportedAlgorithm` for proofs not signed with
+    /// `SIGNATURE_ALGORITHM`.
+    pub fn verify_with_backend(&self, backend: &dyn crate::id::SignatureVerifier) -> Result<()> {
+        check_signature_algorithm(self.signature_algorithm())?;
+        self.ensure_co_signatures_supported()?;
         let pubkey = self.content.author_id();
-        pubkey.verify_signature(self.body.as_bytes(), self.signature())?;
+        pubkey
+            .verify_signature_with(
+                &signed_bytes(&self.body, self.content.version()),
+                self.signature(),
+                backend,
+            )
+            .map_err(|e| {
+                // Note: don't include the body, it could be large
+                format_err!(
+                    "Invalid signature {}... by {} (fingerprint {}): {}",
+                    self.signature().chars().take(16).collect::<String>(),
+                    pubkey,
+                    pubkey.fingerprint(),
+                    e
+                )
+            })?;
+
+        for co_signature in &self.co_signatures {
+            co_signature
+                .id
+                .verify_signature_with(
+                    &signed_bytes(&self.body, self.content.version()),
+                    &co_signature.signature,
+                    backend,
+                )
+                .map_err(|e| {
+                    format_err!(
+                        "Invalid co-signature {}... by {}: {}",
+                        co_signature.signature.chars().take(16).collect::<String>(),
+                        co_signature.id,
+                        e
+                    )
+                })?;
+        }
 
         Ok(())
     }
+
+    /// Like `verify`, but skip proofs already in `cache`
+    ///
+    /// Proofs that verify are added to `cache`.
+    pub fn verify_cached(&self, cache: &mut VerifyCache) -> Result<()> {
+        self.verify_cached_with_backend(cache, &crate::id::Ed25519Verifier)
+    }
+
+    /// Like `verify_cached`, but with signatures checked by `backend`
+    pub fn verify_cached_with_backend(
+        &self,
+        cache: &mut VerifyCache,
+        backend: &dyn crate::id::SignatureVerifier,
+    ) -> Result<()> {
+        let key = VerifyCache::key(self);
+        if cache.verified.contains(&key) {
+            return Ok(());
+        }
+        self.verify_with_backend(backend)?;
+        cache.verified.insert(key);
+        Ok(())
+    }
+
+    pub fn metrics(&self) -> ProofMetrics {
+        ProofMetrics {
+            body_len: self.body.len(),
+            body_lines: self.body.lines().count(),
+            signature_len: self.signature().len(),
+        }
+    }
+
+    /// One line summary, eg. for CLI output
+    ///
+    /// Eg. `[TRUST]   <author>→<id> level=high 2019-08-01`
+    pub fn summary(&self) -> String {
+        use self::review::Common;
+        let date = self.content.date().format("%Y-%m-%d");
+        match self.content {
+            Content::Trust(ref trust) => format!(
+                "{:<9} {}→{} level={} {}",
+                "[TRUST]",
+                trust.author_id(),
+                trust
+                    .ids
+                    .iter()
+                    .map(|id| id.id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+                trust.trust,
+                date
+            ),
+            Content::Code(ref review) => format!(
+                "{:<9} {} {} {} files={} trust={} {}",
+                "[CODE]",
+                review.author_id(),
+                review.project.name,
+                review.project.version,
+                review.files.len(),
+                review.score().trust,
+                date
+            ),
+            Content::Project(ref review) => format!(
+                "{:<9} {} {} {} trust={} {}",
+                "[PROJECT]",
+                review.author_id(),
+                review.project.name,
+                review.project.version,
+                review.score().trust,
+                date
+            ),
+        }
+    }
+
+    /// Is `own` the author of this proof
+    ///
+    /// Compares the keys only; the signature is not checked, see `verify`.
+    pub fn is_signed_by(&self, own: &crate::id::OwnId) -> bool {
+        self.content.author().same_key(&own.id)
+    }
+
+    /// Add a signature of `id` over the same body
+    ///
+    /// Fails for proofs of versions without `Feature::MultiSignature`.
+    pub fn co_sign(&mut self, id: &crate::id::OwnId) -> Result<()> {
+        crate::ensure_version_supports(self.content.version(), crate::Feature::MultiSignature)?;
+        let signature = id.sign(&signed_bytes(&self.body, self.content.version()));
+        self.co_signatures.push(CoSignature {
+            id: id.id.id.clone(),
+            signature: base64::encode_config(&signature, base64::URL_SAFE),
+        });
+        Ok(())
+    }
+
+    /// Fail if co-signed, but of a version without `Feature::MultiSignature`
+    fn ensure_co_signatures_supported(&self) -> Result<()> {
+        if !self.co_signatures.is_empty() {
+            crate::ensure_version_supports(self.content.version(), crate::Feature::MultiSignature)?;
+        }
+        Ok(())
+    }
+
+    /// Pass if at least `n` distinct ids (author included) signed validly
+    ///
+    /// Unlike `verify`, invalid signatures are just not counted.
+    pub fn verify_threshold(&self, n: usize) -> Result<()> {
+        check_signature_algorithm(self.signature_algorithm())?;
+        self.ensure_co_signatures_supported()?;
+        let signed = signed_bytes(&self.body, self.content.version());
+        let author = self.content.author_id();
+        let mut valid = HashSet::new();
+        if author.verify_signature(&signed, self.signature()).is_ok() {
+            valid.insert(author);
+        }
+        for co_signature in &self.co_signatures {
+            if co_signature
+                .id
+                .verify_signature(&signed, &co_signature.signature)
+                .is_ok()
+            {
+                valid.insert(co_signature.id.clone());
+            }
+        }
+        if valid.len() < n {
+            bail!(
+                "Only {} valid distinct signatures, {} required",
+                valid.len(),
+                n
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Update history of `latest`: itself, the proof it supersedes, and so on
+///
+/// Links are followed only within `proofs`, and only between proofs
+/// of the same author. The chain ends at the first missing proof.
+pub fn supersedes_chain<'a>(proofs: &'a [Proof], latest: &'a Proof) -> Vec<&'a Proof> {
+    let mut chain = vec![latest];
+    let mut seen = HashSet::new();
+    seen.insert(&latest.digest);
+    let mut current = latest;
+    while let Some(digest) = current.content.supersedes() {
+        let previous = proofs.iter().find(|proof| {
+            proof.digest.as_slice() == digest
+                && proof.content.author_id() == latest.content.author_id()
+        });
+        match previous {
+            Some(previous) if seen.insert(&previous.digest) => {
+                chain.push(previous);
+                current = previous;
+            }
+            _ => break,
+        }
+    }
+    chain
+}
+
+/// Parse and verify the proofs in `reader`, but at most `max_verifications`
+///
+/// Each proof gets its own result, so one bad proof doesn't hide the
+/// others; a malformed stream ends with its error. Reading stops once the
+/// budget is used up, and the returned flag tells if any proof was left
+/// unverified because of it.
+pub fn verify_stream_budgeted(
+    reader: impl io::BufRead,
+    max_verifications: usize,
+) -> (Vec<Result<Proof>>, bool) {
+    let mut results = vec![];
+    let mut exhausted = false;
+    let parsed = Serialized::parse_each(reader, ParseLimits::default(), |serialized| {
+        if results.len() == max_verifications {
+            exhausted = true;
+            return false;
+        }
+        results.push(serialized.to_parsed().and_then(|proof| {
+            proof.verify()?;
+            Ok(proof)
+        }));
+        true
+    });
+    if let Err(e) = parsed {
+        results.push(Err(e));
+    }
+    (results, exhausted)
+}
+
+/// Authors of `proofs`, each one once, sorted by fingerprint
+pub fn distinct_authors(proofs: &[Proof]) -> Vec<crate::Id> {
+    let mut authors: Vec<_> = proofs
+        .iter()
+        .map(|proof| proof.content.author_id())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    authors.sort_by_cached_key(crate::Id::fingerprint);
+    authors
+}
+
+/// Types of the proofs in `reader`, going by their begin markers only
+///
+/// Much cheaper than parsing, eg. to route files to the right handler.
+/// Nothing is validated: a broken proof still counts.
+pub fn detect_proof_types(reader: impl io::BufRead) -> Result<HashSet<ProofType>> {
+    let mut types = HashSet::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if let Some(&type_) = [ProofType::Code, ProofType::Project, ProofType::Trust]
+            .iter()
+            .find(|type_| line == type_.begin_block())
+        {
+            types.insert(type_);
+        }
+    }
+    Ok(types)
+}
+
+/// Writes proofs out one by one, without buffering them all in memory
+pub struct ProofWriter<W: io::Write> {
+    writer: W,
+}
+
+impl<W: io::Write> ProofWriter<W> {
+    pub fn new(writer: W) -> Self {
+        ProofWriter { writer }
+    }
+
+    pub fn write_proof(&mut self, proof: &Proof) -> io::Result<()> {
+        write!(self.writer, "{}", proof)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
 }
 
 fn equals_default_digest_type(s: &str) -> bool {
@@ -0,0 +1,262 @@
+use crate::proof::{Content, Proof, ProofType};
+use crate::{PubId, Result};
+use chrono::{self, prelude::*};
+use serde_yaml;
+
+/// Signature algorithm of the proofs signed by this version
+pub const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+/// Proof uses an algorithm not allowed by a `VerifyPolicy`
+#[derive(Fail, Debug)]
+#[fail(display = "Disallowed {} algorithm: {}", kind, algorithm)]
+pub struct DisallowedAlgorithm {
+    /// "signature" or "digest"
+    pub kind: &'static str,
+    pub algorithm: String,
+}
+
+/// Proof declares a signature algorithm this version can't verify
+#[derive(Fail, Debug)]
+#[fail(display = "Unsupported signature algorithm: {}", algorithm)]
+pub struct UnsupportedAlgorithm {
+    pub algorithm: String,
+}
+
+/// Something suspicious about a proof that still verifies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// Top-level field of the content unknown to this version, and ignored
+    UnknownField(String),
+    /// Trust expiring within `VerifyPolicy::expiry_warning`
+    TrustExpiresSoon(chrono::DateTime<FixedOffset>),
+    /// Trust that is not valid anymore
+    TrustExpired(chrono::DateTime<FixedOffset>),
+}
+
+/// Result of `Proof::verify_full`
+#[derive(Debug, Clone)]
+pub struct VerifiedOutcome {
+    pub content: Content,
+    pub warnings: Vec<Warning>,
+}
+
+/// Restrictions of what a verifier accepts, on top of a valid signature
+#[derive(Debug, Clone)]
+pub struct VerifyPolicy {
+    pub allowed_signature_algorithms: Vec<String>,
+    pub allowed_digest_algorithms: Vec<String>,
+    /// Match the author against a pinned id by key only, see `Proof::verify_pinned`
+    ///
+    /// Lets authors move their proofs to a new url, without
+    /// invalidating the proofs signed before the move.
+    pub ignore_url: bool,
+    /// Warn about trust expiring within that long, see `Proof::verify_full`
+    pub expiry_warning: chrono::Duration,
+}
+
+impl Default for VerifyPolicy {
+    /// Allow all the algorithms currently in use
+    fn default() -> Self {
+        VerifyPolicy {
+            allowed_signature_algorithms: vec![SIGNATURE_ALGORITHM.into()],
+            allowed_digest_algorithms: vec!["blake2b".into(), "sha256".into()],
+            ignore_url: false,
+            expiry_warning: chrono::Duration::days(30),
+        }
+    }
+}
+
+impl VerifyPolicy {
+    pub fn check(&self, proof: &Proof) -> Result<()> {
+        let signature_algorithm = proof.signature_algorithm();
+        if !self
+            .allowed_signature_algorithms
+            .iter()
+            .any(|a| a == signature_algorithm)
+        {
+            return Err(DisallowedAlgorithm {
+                kind: "signature",
+                algorithm: signature_algorithm.into(),
+            }
+            .into());
+        }
+
+        for digest_algorithm in digest_algorithms(&proof.content) {
+            if !self
+                .allowed_digest_algorithms
+                .iter()
+                .any(|a| a == digest_algorithm)
+            {
+                return Err(DisallowedAlgorithm {
+                    kind: "digest",
+                    algorithm: digest_algorithm.into(),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// All digest algorithms `content` relies on
+fn digest_algorithms(content: &Content) -> Vec<&str> {
+    match content {
+        Content::Trust(_trust) => vec![],
+        Content::Code(review) => review
+            .project
+            .digests()
+            .map(|(digest_type, _)| digest_type)
+            .chain(review.files.iter().map(|file| file.digest_type.as_str()))
+            .collect(),
+        Content::Project(review) => review
+            .project
+            .digests()
+            .map(|(digest_type, _)| digest_type)
+            .collect(),
+    }
+}
+
+/// Top-level fields of the content of each `ProofType`, aliases included
+///
+/// Must be kept in sync with the fields of `Trust`, `review::Code`
+/// (with the flattened `review::Score`) and `review::Project`.
+const TRUST_FIELDS: &[&str] = &[
+    "version",
+    "producer",
+    "date",
+    "from",
+    "ids",
+    "trusted-ids",
+    "fingerprints",
+    "trust",
+    "scopes",
+    "distrust",
+    "expires",
+    "comment",
+];
+const CODE_FIELDS: &[&str] = &[
+    "version",
+    "producer",
+    "date",
+    "from",
+    "project",
+    "thoroughness",
+    "understanding",
+    "trust",
+    "distrust",
+    "comment",
+    "reference",
+    "supersedes",
+    "files",
+];
+const PROJECT_FIELDS: &[&str] = &[
+    "version",
+    "producer",
+    "date",
+    "from",
+    "project",
+    "review",
+    "comment",
+    "reference",
+    "supersedes",
+];
+
+fn known_fields(type_: ProofType) -> &'static [&'static str] {
+    match type_ {
+        ProofType::Trust => TRUST_FIELDS,
+        ProofType::Code => CODE_FIELDS,
+        ProofType::Project => PROJECT_FIELDS,
+    }
+}
+
+/// Top-level fields of `body` that parsing it as `type_` ignores
+fn unknown_fields(body: &str, type_: ProofType) -> Result<Vec<String>> {
+    use serde_yaml::Value;
+
+    let mapping = match serde_yaml::from_str(body)? {
+        Value::Mapping(mapping) => mapping,
+        _ => return Ok(vec![]),
+    };
+    let known = known_fields(type_);
+
+    let mut unknown = vec![];
+    for (key, _value) in mapping {
+        match key {
+            Value::String(ref key) if known.contains(&key.as_str()) => {}
+            Value::String(key) => unknown.push(key),
+            other => unknown.push(serde_yaml::to_string(&other)?),
+        }
+    }
+    Ok(unknown)
+}
+
+impl Proof {
+    /// Verify with `policy`, and report anything suspicious about the proof
+    ///
+    /// What most consumers want: fails like `verify_with_policy`, but
+    /// also returns the verified content along non-fatal `Warning`s.
+    pub fn verify_full(&self, policy: &VerifyPolicy) -> Result<VerifiedOutcome> {
+        self.verify_with_policy(policy)?;
+
+        let mut warnings: Vec<_> = unknown_fields(&self.body, self.content.proof_type())?
+            .into_iter()
+            .map(Warning::UnknownField)
+            .collect();
+        if let Content::Trust(ref trust) = self.content {
+            if let Some(expires) = trust.expires {
+                let now = Utc::now();
+                if trust.is_expired_at(now) {
+                    warnings.push(Warning::TrustExpired(expires));
+                } else if expires <= now + policy.expiry_warning {
+                    warnings.push(Warning::TrustExpiresSoon(expires));
+                }
+            }
+        }
+
+        Ok(VerifiedOutcome {
+            content: self.content.clone(),
+            warnings,
+        })
+    }
+
+    /// Does this build lack something to fully interpret this proof
+    ///
+    /// True for proofs of a version newer than `current_version`, so
+    /// possibly using `Feature`s unknown here, signed with an unsupported
+    /// algorithm, or with content fields unknown to this version. Clients
+    /// should then suggest an update, rather than trust their reading.
+    pub fn requires_newer_client(&self) -> bool {
+        self.content.version() > crate::current_version()
+            || super::check_signature_algorithm(self.signature_algorithm()).is_err()
+            || unknown_fields(&self.body, self.content.proof_type())
+                .map_or(true, |fields| !fields.is_empty())
+    }
+
+    /// Like `verify`, but also fail if `policy` doesn't allow the
+    /// algorithms this proof uses
+    pub fn verify_with_policy(&self, policy: &VerifyPolicy) -> Result<()> {
+        policy.check(self)?;
+        self.verify()
+    }
+    /// Like `verify_with_policy`, but also fail unless authored by `pinned`
+    ///
+    /// Both the key and the url of the author must match, unless
+    /// `policy.ignore_url` is set.
+    pub fn verify_pinned(&self, pinned: &PubId, policy: &VerifyPolicy) -> Result<()> {
+        let author = self.content.author();
+        if !author.same_key(pinned) {
+            bail!(
+                "Proof author {} doesn't match the pinned id {}",
+                author.id,
+                pinned.id
+            );
+        }
+        if !policy.ignore_url && author.url != pinned.url {
+            bail!(
+                "Proof author {} url doesn't match the pinned one",
+                author.id
+            );
+        }
+        self.verify_with_policy(policy)
+    }
+}
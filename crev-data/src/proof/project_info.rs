@@ -1,9 +1,18 @@
-use crate::proof;
+use crate::{proof, Result};
 use base64;
+use serde_yaml;
+use std::{collections::BTreeMap, fmt};
 
-use crev_common::serde::{as_base64, from_base64};
+use crev_common::serde::{as_base64, as_base64_map, from_base64, from_base64_map};
 
+/// Package a review is about
+///
+/// The canonical form (`Display`) is headerless YAML, with the fields in
+/// the order of declaration, the `id` fields inlined, and the fields
+/// equal to their defaults left out. Since it is what ends up in signed
+/// reviews, it must stay stable.
 #[derive(Clone, Debug, Builder, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ProjectInfo {
     #[serde(flatten)]
     pub id: Option<crate::id::PubId>,
@@ -20,6 +29,7 @@ pub struct ProjectInfo {
     )]
     pub revision_type: String,
 
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
     pub digest: Vec<u8>,
     #[serde(
@@ -27,4 +37,82 @@ pub struct ProjectInfo {
         default = "proof::default_digest_type"
     )]
     pub digest_type: String,
+    /// Digests of the same source by other digest types
+    ///
+    /// Lets consumers using either algorithm check the source, eg.
+    /// during a migration to a new `digest_type`. See `matches_digest`.
+    #[cfg_attr(feature = "schema", schemars(with = "BTreeMap<String, String>"))]
+    #[serde(
+        rename = "alt-digests",
+        skip_serializing_if = "BTreeMap::is_empty",
+        default,
+        serialize_with = "as_base64_map",
+        deserialize_with = "from_base64_map"
+    )]
+    #[builder(default = "Default::default()")]
+    pub alt_digests: BTreeMap<String, Vec<u8>>,
+}
+
+impl fmt::Display for ProjectInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crev_common::serde::write_as_headerless_yaml(self, f)
+    }
+}
+
+impl ProjectInfo {
+    pub fn parse(s: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(&s)?)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            bail!("Project without a name");
+        }
+        if self.digest.is_empty() {
+            bail!("Project without a digest");
+        }
+        Ok(())
+    }
+
+    /// Check the digest lengths, see `Content::verify_content_digest`
+    pub fn verify_digest(&self) -> Result<()> {
+        for (digest_type, digest) in self.digests() {
+            crate::digest::check_length(digest, digest_type)
+                .map_err(|e| format_err!("Project {}: {}", self.name, e))?;
+        }
+        Ok(())
+    }
+
+    /// All the recorded digests of the source, `digest` first
+    pub fn digests(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        Some((self.digest_type.as_str(), self.digest.as_slice()))
+            .into_iter()
+            .chain(
+                self.alt_digests
+                    .iter()
+                    .map(|(digest_type, digest)| (digest_type.as_str(), digest.as_slice())),
+            )
+    }
+
+    /// Recorded digest of the source of a given `digest_type`, if any
+    pub fn digest_of(&self, digest_type: &str) -> Option<&[u8]> {
+        self.digests()
+            .find(|&(type_, _)| type_ == digest_type)
+            .map(|(_, digest)| digest)
+    }
+
+    /// Does the source with `digest` of `digest_type` match this project
+    ///
+    /// Any of the recorded digests can match.
+    pub fn matches_digest(&self, digest_type: &str, digest: &[u8]) -> bool {
+        self.digest_of(digest_type) == Some(digest)
+    }
+
+    /// Is `other` the same package, with the same source digest
+    pub fn same_package(&self, other: &ProjectInfo) -> bool {
+        self.source == other.source
+            && self.name == other.name
+            && self.version == other.version
+            && self.digest == other.digest
+    }
 }
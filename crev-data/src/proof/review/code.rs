@@ -4,15 +4,29 @@ use crev_common;
 use serde_yaml;
 use std::{self, default::Default, fmt, path::PathBuf};
 
-use crev_common::serde::{as_base64, as_rfc3339_fixed, from_base64, from_rfc3339_fixed};
+use crev_common::serde::{
+    as_base64, as_base64_opt, as_rfc3339_fixed, from_base64, from_base64_opt, from_rfc3339_fixed,
+};
 
 const BEGIN_BLOCK: &str = "-----BEGIN CODE REVIEW-----";
 const BEGIN_SIGNATURE: &str = "-----BEGIN CODE REVIEW SIGNATURE-----";
 const END_BLOCK: &str = "-----END CODE REVIEW-----";
 
+/// Revision recorded in the proof can't be found in the repository
+///
+/// Typically happens with shallow clones.
+#[derive(Fail, Debug)]
+#[fail(
+    display = "Revision {} not found in the repository (shallow clone?)",
+    _0
+)]
+pub struct RevisionNotFound(pub String);
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct File {
     pub path: PathBuf,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
     pub digest: Vec<u8>,
     #[serde(rename = "digest-type")]
@@ -25,12 +39,18 @@ pub struct File {
 
 /// Body of a Code Review Proof
 #[derive(Clone, Builder, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 // TODO: validate setters(no newlines, etc)
 // TODO: https://github.com/colin-kiegel/rust-derive-builder/issues/136
 /// Unsigned proof of code review
 pub struct Code {
     #[builder(default = "crate::current_version()")]
     version: i64,
+    /// Tool (and its version) that produced this proof
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default = "None")]
+    pub producer: Option<String>,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     #[builder(default = "crev_common::now()")]
     #[serde(
         serialize_with = "as_rfc3339_fixed",
@@ -46,6 +66,20 @@ pub struct Code {
     #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
     #[builder(default = "Default::default()")]
     comment: String,
+    /// Link to a related discussion (issue, thread, etc.)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default = "None")]
+    pub reference: Option<crate::Url>,
+    /// Digest of an earlier proof this one replaces
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        serialize_with = "as_base64_opt",
+        deserialize_with = "from_base64_opt"
+    )]
+    #[builder(default = "None")]
+    pub supersedes: Option<Vec<u8>>,
     #[serde(
         skip_serializing_if = "std::vec::Vec::is_empty",
         default = "std::vec::Vec::new"
@@ -59,6 +93,8 @@ pub struct Code {
 pub struct CodeDraft {
     #[serde(skip_serializing, default = "crate::current_version")]
     version: i64,
+    #[serde(skip_serializing, default)]
+    producer: Option<String>,
     #[serde(
         serialize_with = "as_rfc3339_fixed",
         deserialize_with = "from_rfc3339_fixed"
@@ -70,6 +106,15 @@ pub struct CodeDraft {
     review: super::Score,
     #[serde(default = "Default::default")]
     comment: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub reference: Option<crate::Url>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        serialize_with = "as_base64_opt",
+        deserialize_with = "from_base64_opt"
+    )]
+    pub supersedes: Option<Vec<u8>>,
     #[serde(
         skip_serializing_if = "std::vec::Vec::is_empty",
         default = "std::vec::Vec::new"
@@ -81,11 +126,14 @@ impl From<Code> for CodeDraft {
     fn from(code: Code) -> Self {
         CodeDraft {
             version: code.version,
+            producer: code.producer,
             date: code.date,
             from: code.from,
             project: code.project,
             review: code.review,
             comment: code.comment,
+            reference: code.reference,
+            supersedes: code.supersedes,
             files: code.files,
         }
     }
@@ -95,11 +143,14 @@ impl From<CodeDraft> for Code {
     fn from(code: CodeDraft) -> Self {
         Code {
             version: code.version,
+            producer: code.producer,
             date: code.date,
             from: code.from,
             project: code.project,
             review: code.review,
             comment: code.comment,
+            reference: code.reference,
+            supersedes: code.supersedes,
             files: code.files,
         }
     }
@@ -112,6 +163,9 @@ impl Code {
 }
 
 impl proof::ContentCommon for Code {
+    fn version(&self) -> i64 {
+        self.version
+    }
     fn date(&self) -> &chrono::DateTime<FixedOffset> {
         &self.date
     }
@@ -134,6 +188,43 @@ impl Code {
     pub fn sign_by(self, id: &id::OwnId) -> Result<proof::Proof> {
         proof::Content::from(self).sign_by(id)
     }
+
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    pub(crate) fn comment_mut(&mut self) -> &mut String {
+        &mut self.comment
+    }
+
+    pub(crate) fn date_mut(&mut self) -> &mut chrono::DateTime<FixedOffset> {
+        &mut self.date
+    }
+
+    /// Check if the revision recorded in this review is `commit` in the git
+    /// repository at `repo_path`
+    ///
+    /// `commit` can be anything `git rev-parse` understands (eg. `HEAD`).
+    /// Returns `RevisionNotFound` error if the reviewed revision is not
+    /// present in the repository.
+    #[cfg(feature = "git")]
+    pub fn matches_git_revision(&self, repo_path: &std::path::Path, commit: &str) -> Result<bool> {
+        if self.project.revision_type != proof::default_revision_type() {
+            bail!("Not a git revision type: {}", self.project.revision_type);
+        }
+        let repo = git2::Repository::open(repo_path)?;
+        let commit = repo.revparse_single(commit)?.peel_to_commit()?;
+
+        let reviewed = match repo.revparse_single(&self.project.revision) {
+            Ok(obj) => obj.peel_to_commit()?,
+            Err(ref e) if e.code() == git2::ErrorCode::NotFound => {
+                return Err(RevisionNotFound(self.project.revision.clone()).into())
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(reviewed.id() == commit.id())
+    }
 }
 
 impl CodeDraft {
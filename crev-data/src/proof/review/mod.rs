@@ -10,12 +10,20 @@ pub trait Common: super::ContentCommon {
     fn score(&self) -> &Score;
 }
 
+/// Assessment made by a review
+///
+/// `thoroughness` and `understanding` are `None` when the reviewer
+/// abstained from assessing them, which is different from assessing
+/// them as `Some(Level::None)`. Abstained fields are not serialized.
 #[derive(Clone, Debug, Serialize, Deserialize, Builder)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Score {
-    #[builder(default = "Default::default()")]
-    pub thoroughness: Level,
-    #[builder(default = "Default::default()")]
-    pub understanding: Level,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default = "Some(Default::default())")]
+    pub thoroughness: Option<Level>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default = "Some(Default::default())")]
+    pub understanding: Option<Level>,
     #[builder(default = "Default::default()")]
     pub trust: Level,
     #[builder(default = "proof::default_distrust_level()")]
@@ -29,8 +37,8 @@ pub struct Score {
 impl Default for Score {
     fn default() -> Self {
         Score {
-            thoroughness: Level::Low,
-            understanding: Level::Medium,
+            thoroughness: Some(Level::Low),
+            understanding: Some(Level::Medium),
             trust: Level::Medium,
             distrust: Level::None,
         }
@@ -42,8 +50,8 @@ impl Score {
     }
     pub fn new_default_distrust() -> Self {
         Score {
-            thoroughness: Level::Low,
-            understanding: Level::Medium,
+            thoroughness: Some(Level::Low),
+            understanding: Some(Level::Medium),
             trust: Level::None,
             distrust: Level::Medium,
         }
@@ -2,7 +2,7 @@ use crate::{id, proof, Result};
 use chrono::{self, prelude::*};
 use crev_common::{
     self,
-    serde::{as_rfc3339_fixed, from_rfc3339_fixed},
+    serde::{as_base64_opt, as_rfc3339_fixed, from_base64_opt, from_rfc3339_fixed},
 };
 use serde_yaml;
 use std::{default::Default, fmt};
@@ -13,12 +13,18 @@ const END_BLOCK: &str = "-----END CREV PROJECT REVIEW-----";
 
 /// Body of a Project Review Proof
 #[derive(Clone, Builder, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 // TODO: validate setters(no newlines, etc)
 // TODO: https://github.com/colin-kiegel/rust-derive-builder/issues/136
 /// Unsigned proof of code review
 pub struct Project {
     #[builder(default = "crate::current_version()")]
     version: i64,
+    /// Tool (and its version) that produced this proof
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default = "None")]
+    pub producer: Option<String>,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     #[builder(default = "crev_common::now()")]
     #[serde(
         serialize_with = "as_rfc3339_fixed",
@@ -33,6 +39,20 @@ pub struct Project {
     #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
     #[builder(default = "Default::default()")]
     comment: String,
+    /// Link to a related discussion (issue, thread, etc.)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default = "None")]
+    pub reference: Option<crate::Url>,
+    /// Digest of an earlier proof this one replaces
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        serialize_with = "as_base64_opt",
+        deserialize_with = "from_base64_opt"
+    )]
+    #[builder(default = "None")]
+    pub supersedes: Option<Vec<u8>>,
 }
 
 /// Like `Project` but serializes for interactive editing
@@ -40,6 +60,8 @@ pub struct Project {
 pub struct ProjectDraft {
     #[serde(skip_serializing, default = "crate::current_version")]
     version: i64,
+    #[serde(skip_serializing, default)]
+    producer: Option<String>,
     #[serde(
         serialize_with = "as_rfc3339_fixed",
         deserialize_with = "from_rfc3339_fixed"
@@ -51,17 +73,29 @@ pub struct ProjectDraft {
     review: super::Score,
     #[serde(default = "Default::default")]
     comment: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub reference: Option<crate::Url>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        serialize_with = "as_base64_opt",
+        deserialize_with = "from_base64_opt"
+    )]
+    pub supersedes: Option<Vec<u8>>,
 }
 
 impl From<Project> for ProjectDraft {
     fn from(project: Project) -> Self {
         ProjectDraft {
             version: project.version,
+            producer: project.producer,
             date: project.date,
             from: project.from,
             project: project.project,
             review: project.review,
             comment: project.comment,
+            reference: project.reference,
+            supersedes: project.supersedes,
         }
     }
 }
@@ -70,11 +104,14 @@ impl From<ProjectDraft> for Project {
     fn from(project: ProjectDraft) -> Self {
         Project {
             version: project.version,
+            producer: project.producer,
             date: project.date,
             from: project.from,
             project: project.project,
             review: project.review,
             comment: project.comment,
+            reference: project.reference,
+            supersedes: project.supersedes,
         }
     }
 }
@@ -86,6 +123,9 @@ impl Project {
 }
 
 impl proof::ContentCommon for Project {
+    fn version(&self) -> i64 {
+        self.version
+    }
     fn date(&self) -> &chrono::DateTime<FixedOffset> {
         &self.date
     }
@@ -109,6 +149,25 @@ impl Project {
     pub fn sign_by(self, id: &id::OwnId) -> Result<proof::Proof> {
         proof::Content::from(self).sign_by(id)
     }
+
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    pub(crate) fn comment_mut(&mut self) -> &mut String {
+        &mut self.comment
+    }
+
+    pub(crate) fn date_mut(&mut self) -> &mut chrono::DateTime<FixedOffset> {
+        &mut self.date
+    }
+
+    /// Does this review cover package `name` at `version`
+    ///
+    /// Names are compared case-insensitively, versions exactly.
+    pub fn matches_package(&self, name: &str, version: &str) -> bool {
+        self.project.name.to_lowercase() == name.to_lowercase() && self.project.version == version
+    }
 }
 
 impl ProjectDraft {
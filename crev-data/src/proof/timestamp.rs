@@ -0,0 +1,110 @@
+//! Timestamps attached to proofs after they were signed
+//!
+//! A timestamp is a signature of a timestamping id over the digest of
+//! a proof and a date, showing the proof existed at that date. Useful
+//! for proofs signed offline, with a date nobody else can vouch for.
+
+use crate::{
+    id::OwnId,
+    proof::{AnnotatedProof, Proof},
+    Id, Result,
+};
+use base64;
+use chrono::{self, prelude::*};
+
+/// Annotation key `AnnotatedProof::timestamp` is stored under
+pub const TIMESTAMP_ANNOTATION: &str = "timestamp";
+
+const TIMESTAMP_DOMAIN_PREFIX: &str = "crev-timestamp-v1\n";
+
+/// Signature of `by` showing a proof existed at `date`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timestamp {
+    pub date: chrono::DateTime<FixedOffset>,
+    pub by: Id,
+    pub signature: Vec<u8>,
+}
+
+fn timestamp_signed_bytes(proof: &Proof, date: &chrono::DateTime<FixedOffset>) -> Vec<u8> {
+    format!(
+        "{}{}\n{}\n",
+        TIMESTAMP_DOMAIN_PREFIX,
+        base64::encode_config(&proof.digest, base64::URL_SAFE),
+        date.to_rfc3339()
+    )
+    .into_bytes()
+}
+
+impl Timestamp {
+    /// Timestamp `proof` with `date`, as the timestamping id `by`
+    pub fn create(proof: &Proof, date: chrono::DateTime<FixedOffset>, by: &OwnId) -> Self {
+        Timestamp {
+            signature: by.sign(&timestamp_signed_bytes(proof, &date)),
+            date,
+            by: by.id.id.clone(),
+        }
+    }
+
+    /// Fail unless this is a valid timestamp of `proof`
+    pub fn verify(&self, proof: &Proof) -> Result<()> {
+        self.by.verify_signature(
+            &timestamp_signed_bytes(proof, &self.date),
+            &base64::encode_config(&self.signature, base64::URL_SAFE),
+        )
+    }
+
+    /// Compact form stored in annotations: `<date> <id> <signature>`
+    fn to_annotation(&self) -> String {
+        format!(
+            "{} {} {}",
+            self.date.to_rfc3339(),
+            self.by,
+            base64::encode_config(&self.signature, base64::URL_SAFE)
+        )
+    }
+
+    fn from_annotation(s: &str) -> Result<Self> {
+        let parts: Vec<_> = s.split_whitespace().collect();
+        if parts.len() != 3 {
+            bail!("Malformed timestamp: {}", s);
+        }
+        Ok(Timestamp {
+            date: chrono::DateTime::parse_from_rfc3339(parts[0])?,
+            by: Id::crevid_from_str(parts[1])?,
+            signature: base64::decode_config(parts[2], base64::URL_SAFE)?,
+        })
+    }
+}
+
+impl AnnotatedProof {
+    /// Timestamp attached with `attach_timestamp`, if any
+    pub fn timestamp(&self) -> Result<Option<Timestamp>> {
+        self.annotations
+            .get(TIMESTAMP_ANNOTATION)
+            .map(|s| Timestamp::from_annotation(s))
+            .transpose()
+    }
+
+    /// Attach `timestamp`, failing if it's not a valid timestamp of the proof
+    ///
+    /// The signed proof block is left intact, so this can be done any
+    /// time after signing, eg. once an offline-signed proof is online.
+    pub fn attach_timestamp(&mut self, timestamp: &Timestamp) -> Result<()> {
+        timestamp.verify(&self.proof)?;
+        self.annotations
+            .insert(TIMESTAMP_ANNOTATION.into(), timestamp.to_annotation());
+        Ok(())
+    }
+
+    /// Verify the proof and its timestamp, returning the timestamp
+    ///
+    /// Proofs without a timestamp fail too.
+    pub fn verify_timestamped(&self) -> Result<Timestamp> {
+        self.verify()?;
+        let timestamp = self
+            .timestamp()?
+            .ok_or_else(|| format_err!("No timestamp attached"))?;
+        timestamp.verify(&self.proof)?;
+        Ok(timestamp)
+    }
+}
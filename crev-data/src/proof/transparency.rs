@@ -0,0 +1,184 @@
+//! Inclusion of proofs in an append-only (transparency) log
+//!
+//! The log is a Merkle tree over proof digests, hashed like in
+//! RFC 6962, but with blake2b. An `InclusionProof` shows that a proof
+//! is in the log with a given root hash, so consumers can detect a log
+//! showing different contents to different people.
+
+use crate::{proof::AnnotatedProof, Result};
+use base64;
+use blake2::{digest::FixedOutput, Digest};
+
+/// Annotation key `AnnotatedProof::inclusion_proof` is stored under
+pub const INCLUSION_PROOF_ANNOTATION: &str = "log-inclusion";
+
+fn leaf_hash(leaf: &[u8]) -> Vec<u8> {
+    let mut hasher = blake2::Blake2b::new();
+    hasher.input(&[0]);
+    hasher.input(leaf);
+    hasher.fixed_result().to_vec()
+}
+
+fn node_hash(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut hasher = blake2::Blake2b::new();
+    hasher.input(&[1]);
+    hasher.input(left);
+    hasher.input(right);
+    hasher.fixed_result().to_vec()
+}
+
+/// Largest power of two smaller than `n`, for `n > 1`
+fn split_point(n: usize) -> usize {
+    let mut k = 1;
+    while k * 2 < n {
+        k *= 2;
+    }
+    k
+}
+
+/// Root hash of a log containing `leaves` (proof digests), in order
+pub fn merkle_root(leaves: &[Vec<u8>]) -> Vec<u8> {
+    match leaves.len() {
+        0 => crev_common::blake2sum(&[]),
+        1 => leaf_hash(&leaves[0]),
+        n => {
+            let k = split_point(n);
+            node_hash(&merkle_root(&leaves[..k]), &merkle_root(&leaves[k..]))
+        }
+    }
+}
+
+/// Position of a proof in a log, with the audit path to its root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionProof {
+    pub index: u64,
+    pub tree_size: u64,
+    /// Hashes of the siblings along the way to the root, bottom up
+    pub path: Vec<Vec<u8>>,
+}
+
+impl InclusionProof {
+    /// Inclusion proof of `leaves[index]` in a log of `leaves`
+    pub fn build(leaves: &[Vec<u8>], index: usize) -> Option<Self> {
+        fn path(leaves: &[Vec<u8>], index: usize) -> Vec<Vec<u8>> {
+            if leaves.len() <= 1 {
+                return vec![];
+            }
+            let k = split_point(leaves.len());
+            if index < k {
+                let mut res = path(&leaves[..k], index);
+                res.push(merkle_root(&leaves[k..]));
+                res
+            } else {
+                let mut res = path(&leaves[k..], index - k);
+                res.push(merkle_root(&leaves[..k]));
+                res
+            }
+        }
+
+        if index >= leaves.len() {
+            return None;
+        }
+        Some(InclusionProof {
+            index: index as u64,
+            tree_size: leaves.len() as u64,
+            path: path(leaves, index),
+        })
+    }
+
+    /// Root hash of the log, if `leaf` is where this proof says it is
+    pub fn root_for(&self, leaf: &[u8]) -> Result<Vec<u8>> {
+        if self.index >= self.tree_size {
+            bail!(
+                "Log index {} out of range of {} entries",
+                self.index,
+                self.tree_size
+            );
+        }
+        let mut f = self.index;
+        let mut s = self.tree_size - 1;
+        let mut hash = leaf_hash(leaf);
+        for sibling in &self.path {
+            if s == 0 {
+                bail!("Inclusion proof path too long");
+            }
+            if f & 1 == 1 || f == s {
+                hash = node_hash(sibling, &hash);
+                while f & 1 == 0 && f != 0 {
+                    f >>= 1;
+                    s >>= 1;
+                }
+            } else {
+                hash = node_hash(&hash, sibling);
+            }
+            f >>= 1;
+            s >>= 1;
+        }
+        if s != 0 {
+            bail!("Inclusion proof path too short");
+        }
+        Ok(hash)
+    }
+
+    /// Fail unless `leaf` is included in the log with `root_hash`
+    pub fn verify(&self, leaf: &[u8], root_hash: &[u8]) -> Result<()> {
+        if self.root_for(leaf)? != root_hash {
+            bail!("Proof not included in the log at index {}", self.index);
+        }
+        Ok(())
+    }
+
+    /// Compact form stored in annotations: `<index> <tree-size> <hash>...`
+    fn to_annotation(&self) -> String {
+        let mut res = format!("{} {}", self.index, self.tree_size);
+        for hash in &self.path {
+            res += " ";
+            res += &base64::encode_config(hash, base64::URL_SAFE);
+        }
+        res
+    }
+
+    fn from_annotation(s: &str) -> Result<Self> {
+        let mut parts = s.split_whitespace();
+        let mut number = || -> Result<u64> {
+            Ok(parts
+                .next()
+                .ok_or_else(|| format_err!("Truncated inclusion proof"))?
+                .parse()?)
+        };
+        let index = number()?;
+        let tree_size = number()?;
+        let path = parts
+            .map(|hash| base64::decode_config(hash, base64::URL_SAFE))
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(InclusionProof {
+            index,
+            tree_size,
+            path,
+        })
+    }
+}
+
+impl AnnotatedProof {
+    /// Where the proof is in a transparency log, if recorded
+    pub fn inclusion_proof(&self) -> Result<Option<InclusionProof>> {
+        self.annotations
+            .get(INCLUSION_PROOF_ANNOTATION)
+            .map(|s| InclusionProof::from_annotation(s))
+            .transpose()
+    }
+
+    pub fn set_inclusion_proof(&mut self, inclusion: &InclusionProof) {
+        self.annotations
+            .insert(INCLUSION_PROOF_ANNOTATION.into(), inclusion.to_annotation());
+    }
+
+    /// Fail unless the proof is in the log with `root_hash`
+    ///
+    /// Proofs without a recorded inclusion proof fail too.
+    pub fn verify_inclusion(&self, root_hash: &[u8]) -> Result<()> {
+        self.inclusion_proof()?
+            .ok_or_else(|| format_err!("No inclusion proof recorded"))?
+            .verify(&self.proof.digest, root_hash)
+    }
+}
@@ -2,10 +2,10 @@ use crate::{id, level::Level, proof, Result};
 use chrono::{self, prelude::*};
 use crev_common::{
     self,
-    serde::{as_rfc3339_fixed, from_rfc3339_fixed},
+    serde::{as_rfc3339_fixed, as_rfc3339_fixed_opt, from_rfc3339_fixed, from_rfc3339_fixed_opt},
 };
 use serde_yaml;
-use std::fmt;
+use std::{collections::BTreeMap, fmt, io};
 
 const BEGIN_BLOCK: &str = "-----BEGIN CREV TRUST -----";
 const BEGIN_SIGNATURE: &str = "-----BEGIN CREV TRUST SIGNATURE-----";
@@ -13,9 +13,15 @@ const END_BLOCK: &str = "-----END CREV TRUST-----";
 
 /// Body of a Trust Proof
 #[derive(Clone, Debug, Builder, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Trust {
     #[builder(default = "crate::current_version()")]
     version: i64,
+    /// Tool (and its version) that produced this proof
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[builder(default = "None")]
+    pub producer: Option<String>,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     #[builder(default = "crev_common::now()")]
     #[serde(
         serialize_with = "as_rfc3339_fixed",
@@ -23,18 +29,44 @@ pub struct Trust {
     )]
     pub date: chrono::DateTime<FixedOffset>,
     pub from: crate::PubId,
+    #[serde(alias = "trusted-ids")]
     pub ids: Vec<crate::PubId>,
+    /// Ids trusted while only their fingerprint is known
+    ///
+    /// See `Trust::resolve_fingerprints`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    #[builder(default = "Default::default()")]
+    pub fingerprints: Vec<crate::Fingerprint>,
     #[builder(default = "Default::default()")]
     pub trust: Level,
+    /// Levels of trust in specific areas (eg. "security"), overriding `trust`
+    ///
+    /// See `Trust::level_for`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    #[builder(default = "Default::default()")]
+    pub scopes: BTreeMap<String, Level>,
     #[builder(default = "proof::default_distrust_level()")]
     #[serde(
         skip_serializing_if = "proof::equals_default_distrust_level",
         default = "proof::default_distrust_level"
     )]
     pub distrust: Level,
+    /// When this trust stops being valid, if ever
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        serialize_with = "as_rfc3339_fixed_opt",
+        deserialize_with = "from_rfc3339_fixed_opt"
+    )]
+    #[builder(default = "None")]
+    pub expires: Option<chrono::DateTime<FixedOffset>>,
+    /// Justification of the trust, eg. "verified in person"
+    ///
+    /// Empty means no comment, and is not serialized.
     #[serde(skip_serializing_if = "String::is_empty", default = "Default::default")]
     #[builder(default = "Default::default()")]
-    comment: String,
+    pub comment: String,
 }
 
 /// Like `Trust` but serializes for interactive editing
@@ -42,36 +74,54 @@ pub struct Trust {
 pub struct TrustDraft {
     #[serde(skip_serializing, default = "crate::current_version")]
     version: i64,
+    #[serde(skip_serializing, default)]
+    producer: Option<String>,
     #[serde(
         serialize_with = "as_rfc3339_fixed",
         deserialize_with = "from_rfc3339_fixed"
     )]
     pub date: chrono::DateTime<FixedOffset>,
     pub from: crate::PubId,
+    #[serde(alias = "trusted-ids")]
     pub ids: Vec<crate::PubId>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub fingerprints: Vec<crate::Fingerprint>,
     #[serde(
         skip_serializing_if = "proof::equals_none_level",
         default = "proof::none_level"
     )]
     pub trust: Level,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub scopes: BTreeMap<String, Level>,
     #[serde(
         skip_serializing_if = "proof::equals_none_level",
         default = "proof::none_level"
     )]
     pub distrust: Level,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        serialize_with = "as_rfc3339_fixed_opt",
+        deserialize_with = "from_rfc3339_fixed_opt"
+    )]
+    pub expires: Option<chrono::DateTime<FixedOffset>>,
     #[serde(default = "Default::default")]
-    comment: String,
+    pub comment: String,
 }
 
 impl From<Trust> for TrustDraft {
     fn from(trust: Trust) -> Self {
         TrustDraft {
             version: trust.version,
+            producer: trust.producer,
             date: trust.date,
             from: trust.from,
             ids: trust.ids,
+            fingerprints: trust.fingerprints,
             trust: trust.trust,
+            scopes: trust.scopes,
             distrust: trust.distrust,
+            expires: trust.expires,
             comment: trust.comment,
         }
     }
@@ -81,11 +131,15 @@ impl From<TrustDraft> for Trust {
     fn from(trust: TrustDraft) -> Self {
         Trust {
             version: trust.version,
+            producer: trust.producer,
             date: trust.date,
             from: trust.from,
             ids: trust.ids,
+            fingerprints: trust.fingerprints,
             trust: trust.trust,
+            scopes: trust.scopes,
             distrust: trust.distrust,
+            expires: trust.expires,
             comment: trust.comment,
         }
     }
@@ -109,6 +163,9 @@ impl Trust {
 }
 
 impl proof::ContentCommon for Trust {
+    fn version(&self) -> i64 {
+        self.version
+    }
     fn date(&self) -> &chrono::DateTime<FixedOffset> {
         &self.date
     }
@@ -120,12 +177,233 @@ impl proof::ContentCommon for Trust {
 
 impl Trust {
     pub fn parse(s: &str) -> Result<Self> {
-        Ok(serde_yaml::from_str(&s)?)
+        let trust: Self = serde_yaml::from_str(&s)?;
+        trust.ensure_features_supported()?;
+        Ok(trust)
+    }
+
+    /// Fail if this uses features its `version` doesn't have
+    pub(crate) fn ensure_features_supported(&self) -> Result<()> {
+        if self.expires.is_some() {
+            crate::ensure_version_supports(self.version, crate::Feature::TrustExpiration)?;
+        }
+        Ok(())
     }
 
     pub fn sign_by(self, id: &id::OwnId) -> Result<proof::Proof> {
         super::Content::from(self).sign_by(id)
     }
+
+    /// Bind fingerprint-only targets to the `known` ids matching them
+    ///
+    /// Returns the ids (among `known`) this proof trusts by fingerprint.
+    pub fn resolve_fingerprints<'a>(&self, known: &'a [crate::PubId]) -> Vec<&'a crate::PubId> {
+        known
+            .iter()
+            .filter(|id| self.fingerprints.contains(&id.id.fingerprint()))
+            .collect()
+    }
+
+    /// Level of trust in `scope`, or the overall one if not scoped
+    pub fn level_for(&self, scope: Option<&str>) -> &Level {
+        scope
+            .and_then(|scope| self.scopes.get(scope))
+            .unwrap_or(&self.trust)
+    }
+
+    /// Has this trust expired at `now`
+    pub fn is_expired_at(&self, now: chrono::DateTime<Utc>) -> bool {
+        self.expires.map(|expires| expires <= now).unwrap_or(false)
+    }
+
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    pub(crate) fn comment_mut(&mut self) -> &mut String {
+        &mut self.comment
+    }
+}
+
+/// All trust levels `target` received in `proofs`
+///
+/// Returns `(truster, level, date)` for each trust proof including `target`,
+/// in the order of `proofs`. Other proofs are ignored.
+pub fn trust_entries_for(
+    proofs: &[proof::Proof],
+    target: &crate::Id,
+) -> Vec<(crate::Id, Level, chrono::DateTime<Utc>)> {
+    use crate::proof::ContentCommon;
+    proofs
+        .iter()
+        .filter_map(|proof| match proof.content {
+            proof::Content::Trust(ref trust) => Some(trust),
+            _ => None,
+        })
+        .filter(|trust| trust.ids.iter().any(|id| id.id == *target))
+        .map(|trust| (trust.author_id(), trust.trust.clone(), trust.date_utc()))
+        .collect()
+}
+
+/// Trust proofs expiring within `within` from `now`
+///
+/// Eg. to remind the user to renew them. Proofs already expired
+/// at `now`, or without an expiration date, are not included.
+pub fn expiring_trust_proofs(
+    proofs: &[proof::Proof],
+    within: chrono::Duration,
+    now: chrono::DateTime<Utc>,
+) -> Vec<proof::Proof> {
+    proofs
+        .iter()
+        .filter(|proof| match proof.content {
+            proof::Content::Trust(ref trust) => match trust.expires {
+                Some(expires) => now < expires && expires <= now + within,
+                None => false,
+            },
+            _ => false,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Find a chain of trust from any of `roots` to `target`
+///
+/// Every id in the chain must trust the next one at `min_level` or higher
+/// (so the level of the whole chain is the lowest along it). Only the newest
+/// trust proof from one id about another counts. Signatures are not verified.
+///
+/// Returns the ids of the shortest such chain, from a root to `target`.
+pub fn verify_trust_path(
+    proofs: &[proof::Proof],
+    roots: &[crate::Id],
+    target: &crate::Id,
+    min_level: Level,
+) -> Option<Vec<crate::Id>> {
+    use crate::proof::ContentCommon;
+    use std::collections::{HashMap, HashSet, VecDeque};
+
+    // newest trust proof per (truster, trusted) pair
+    let mut edges: HashMap<(crate::Id, crate::Id), &Trust> = HashMap::new();
+    for proof in proofs {
+        if let proof::Content::Trust(ref trust) = proof.content {
+            for id in &trust.ids {
+                let key = (trust.author_id(), id.id.clone());
+                let newer = edges
+                    .get(&key)
+                    .map(|existing| existing.date_utc() < trust.date_utc())
+                    .unwrap_or(true);
+                if newer {
+                    edges.insert(key, trust);
+                }
+            }
+        }
+    }
+
+    let mut next: HashMap<&crate::Id, Vec<&crate::Id>> = HashMap::new();
+    for ((from, to), trust) in &edges {
+        if trust.trust >= min_level {
+            next.entry(from).or_insert_with(Vec::new).push(to);
+        }
+    }
+
+    let mut previous: HashMap<&crate::Id, &crate::Id> = HashMap::new();
+    let mut visited: HashSet<&crate::Id> = roots.iter().collect();
+    let mut queue: VecDeque<&crate::Id> = roots.iter().collect();
+    while let Some(id) = queue.pop_front() {
+        if id == target {
+            let mut path = vec![id.clone()];
+            let mut current = id;
+            while let Some(&prev) = previous.get(current) {
+                path.push(prev.clone());
+                current = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for &to in next.get(id).into_iter().flatten() {
+            if visited.insert(to) {
+                previous.insert(to, id);
+                queue.push_back(to);
+            }
+        }
+    }
+    None
+}
+
+/// `from` trusts `to` at `level`, according to a verified trust proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustEdge {
+    pub from: crate::Id,
+    pub to: crate::Id,
+    pub level: Level,
+    pub date: chrono::DateTime<Utc>,
+}
+
+/// Trust edges from all the validly signed trust proofs in `reader`
+///
+/// Proofs that don't parse or verify are dropped, and their errors
+/// returned next to the edges. Other kinds of proofs are ignored, as
+/// are targets known only by fingerprint. Fails only if the stream
+/// of proofs itself is malformed.
+pub fn build_trust_edges_verified(
+    reader: impl io::BufRead,
+) -> Result<(Vec<TrustEdge>, Vec<failure::Error>)> {
+    use crate::proof::ContentCommon;
+
+    let mut edges = vec![];
+    let mut rejected = vec![];
+    for serialized in proof::Serialized::parse(reader)? {
+        if serialized.type_ != proof::ProofType::Trust {
+            continue;
+        }
+        let proof = match serialized.to_parsed().and_then(|proof| {
+            proof.verify()?;
+            Ok(proof)
+        }) {
+            Ok(proof) => proof,
+            Err(e) => {
+                rejected.push(e);
+                continue;
+            }
+        };
+        if let proof::Content::Trust(ref trust) = proof.content {
+            edges.extend(trust.ids.iter().map(|id| TrustEdge {
+                from: trust.author_id(),
+                to: id.id.clone(),
+                level: trust.trust.clone(),
+                date: trust.date_utc(),
+            }));
+        }
+    }
+    Ok((edges, rejected))
+}
+
+/// `edges` plus a `Level::High` self-trust edge for each of `roots`
+///
+/// Every id implicitly trusts itself, so with those graph algorithms
+/// don't need to special-case roots. Self-edges of `roots` already in
+/// `edges` are replaced. The added edges are dated at the Unix epoch.
+pub fn with_self_trust(edges: &[TrustEdge], roots: &[crate::Id]) -> Vec<TrustEdge> {
+    let mut res: Vec<_> = edges
+        .iter()
+        .filter(|edge| !(edge.from == edge.to && roots.contains(&edge.from)))
+        .cloned()
+        .collect();
+    for root in roots {
+        if !res
+            .iter()
+            .any(|edge| edge.from == *root && edge.to == *root)
+        {
+            res.push(TrustEdge {
+                from: root.clone(),
+                to: root.clone(),
+                level: Level::High,
+                date: Utc.timestamp(0, 0),
+            });
+        }
+    }
+    res
 }
 
 impl TrustDraft {
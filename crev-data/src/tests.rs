@@ -72,17 +72,28 @@ sig2
     Ok(())
 }
 
+pub fn generate_project_info(revision: &str) -> proof::ProjectInfo {
+    proof::ProjectInfo {
+        id: None,
+        source: "https://crates.io".into(),
+        name: "foo".into(),
+        version: "0.1.0".into(),
+        revision: revision.into(),
+        revision_type: "git".into(),
+        digest: vec![0, 1, 2, 3],
+        digest_type: "blake2b".into(),
+        alt_digests: Default::default(),
+    }
+}
+
 pub fn generate_id_and_proof() -> Result<(OwnId, Proof)> {
     let id = OwnId::generate("https://mypage.com/trust.git".into());
 
     //let mut from = crate::PubId::new(&id.id, "https://github.com/someone/crev-trust".into());
 
-    let project_id = proof::Project::generate();
     let review = proof::review::CodeBuilder::default()
         .from(id.id.to_owned())
-        .revision("foobar".into())
-        .revision_type("git".into())
-        .project(project_id)
+        .project(generate_project_info("foobar"))
         .comment("comment".into())
         .files(vec![
             proof::review::code::File {
@@ -104,6 +115,80 @@ pub fn generate_id_and_proof() -> Result<(OwnId, Proof)> {
     Ok((id, proof))
 }
 
+#[test]
+pub fn sign_by_rejects_unparseable_body() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let review_with_comment = |len: usize| -> Result<proof::review::Code> {
+        proof::review::CodeBuilder::default()
+            .from(id.id.to_owned())
+            .project(generate_project_info("foobar"))
+            .comment("a".repeat(len))
+            .build()
+            .map_err(|e| format_err!("{}", e))
+    };
+
+    let fits = review_with_comment(15_000)?;
+    fits.sign_by(&id)?;
+
+    let too_long = review_with_comment(17_000)?;
+    let content = proof::Content::from(too_long.clone());
+    assert!(content.serialized_len() > proof::ParseLimits::default().max_body_len);
+    assert!(content.fits_parse_limits().is_err());
+    let err = too_long.sign_by(&id).unwrap_err();
+    assert!(err.to_string().contains("trim"));
+    assert!(content.sign_by_at(&id, *content.date()).is_err());
+
+    // custom limits apply when parsing
+    let proof = review_with_comment(10)?.sign_by(&id)?;
+    let strict = proof::ParseLimits {
+        max_body_len: 100,
+        ..Default::default()
+    };
+    assert!(Proof::parse_with_limits(proof.to_string().as_bytes(), strict).is_err());
+    assert_eq!(
+        Proof::parse_with_limits(proof.to_string().as_bytes(), Default::default())?.len(),
+        1
+    );
+    Ok(())
+}
+
+#[test]
+pub fn review_abstain_vs_explicit_none() -> Result<()> {
+    use crate::{proof::review::Common, Level};
+
+    let id = OwnId::generate_for_test();
+    let review_with = |thoroughness: Option<Level>| -> Result<Proof> {
+        proof::review::CodeBuilder::default()
+            .from(id.id.to_owned())
+            .project(generate_project_info("foobar"))
+            .review(proof::review::Score {
+                thoroughness,
+                ..Default::default()
+            })
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(&id)
+    };
+    let parsed_thoroughness = |proof: &Proof| -> Result<Option<Level>> {
+        match Proof::parse(proof.to_string().as_bytes())?
+            .remove(0)
+            .content
+        {
+            proof::Content::Code(review) => Ok(review.score().thoroughness.clone()),
+            _ => bail!("expected a code review"),
+        }
+    };
+
+    let abstained = review_with(None)?;
+    assert!(!abstained.body.contains("thoroughness"));
+    assert_eq!(parsed_thoroughness(&abstained)?, None);
+
+    let none = review_with(Some(Level::None))?;
+    assert!(none.body.contains("thoroughness: none"));
+    assert_eq!(parsed_thoroughness(&none)?, Some(Level::None));
+    Ok(())
+}
+
 #[test]
 pub fn sign_proof_review() -> Result<()> {
     let (_id, proof) = generate_id_and_proof()?;
@@ -124,3 +209,2336 @@ pub fn verify_works() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+pub fn verify_error_has_key_details() -> Result<()> {
+    let (_id, mut proof) = generate_id_and_proof()?;
+    proof.body += "\n";
+
+    let err = proof.verify().unwrap_err().to_string();
+    let author = proof.content.author_id();
+    assert!(err.contains(&author.to_string()));
+    assert!(err.contains(&author.fingerprint().to_string()));
+    assert!(err.contains(&proof.signature()[..16]));
+    assert!(!err.contains(proof.body.trim()));
+    Ok(())
+}
+
+#[test]
+pub fn trust_parse_renamed_field_alias() -> Result<()> {
+    let s = r#"
+version: -99999
+date: "2018-12-01T10:00:00+00:00"
+from:
+  id-type: crev
+  id: An9CIxHs1bLYW_VnrYOoy7jdBY105YCvr4AMeNxO_uE=
+  url: "https://github.com/dpc/trust"
+trusted-ids:
+  - id-type: crev
+    id: IkmxqWrukzjbxK9CM6UgAwMDF9AQdotoRHOIoR-zeNI=
+trust: high
+"#;
+
+    let trust = proof::Trust::parse(s)?;
+    assert_eq!(trust.ids.len(), 1);
+
+    let reserialized = trust.to_string();
+    assert!(reserialized.contains("\nids:\n"));
+    assert!(!reserialized.contains("trusted-ids"));
+    Ok(())
+}
+
+#[cfg(feature = "git")]
+#[test]
+pub fn code_review_matches_git_revision() -> Result<()> {
+    let tmp_dir = tempdir::TempDir::new("crev-data-test")?;
+    let repo = git2::Repository::init(tmp_dir.path())?;
+    let sig = git2::Signature::now("crev", "crev@example.com")?;
+    let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+    let first = repo.commit(Some("HEAD"), &sig, &sig, "first", &tree, &[])?;
+    let first_commit = repo.find_commit(first)?;
+    let second = repo.commit(Some("HEAD"), &sig, &sig, "second", &tree, &[&first_commit])?;
+
+    let id = OwnId::generate("https://mypage.com/trust.git".into());
+    let review = proof::review::CodeBuilder::default()
+        .from(id.id.to_owned())
+        .project(generate_project_info(&first.to_string()))
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+
+    assert!(review.matches_git_revision(tmp_dir.path(), &first.to_string())?);
+    assert!(!review.matches_git_revision(tmp_dir.path(), &second.to_string())?);
+    assert!(!review.matches_git_revision(tmp_dir.path(), "HEAD")?);
+
+    let missing = proof::review::CodeBuilder::default()
+        .from(id.id.to_owned())
+        .project(generate_project_info(
+            "0123456789abcdef0123456789abcdef01234567",
+        ))
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+    let err = missing
+        .matches_git_revision(tmp_dir.path(), "HEAD")
+        .unwrap_err();
+    assert!(err
+        .downcast_ref::<proof::review::code::RevisionNotFound>()
+        .is_some());
+    Ok(())
+}
+
+#[test]
+pub fn content_kind_str() -> Result<()> {
+    let (id, proof) = generate_id_and_proof()?;
+    assert_eq!(proof.content.kind_str(), "code");
+
+    let project = proof::review::ProjectBuilder::default()
+        .from(id.id.to_owned())
+        .project(generate_project_info("foobar"))
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+    assert_eq!(proof::Content::from(project).kind_str(), "project");
+
+    let trust = proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .ids(vec![])
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+    assert_eq!(proof::Content::from(trust).kind_str(), "trust");
+    Ok(())
+}
+
+fn sign_without_domain_prefix(id: &OwnId, content: proof::Content) -> Proof {
+    let body = content.to_string();
+    Proof {
+        digest: crev_common::blake2sum(body.as_bytes()),
+        signature: base64::encode_config(&id.sign(body.as_bytes()), base64::URL_SAFE),
+        co_signatures: vec![],
+        signature_algorithm: None,
+        body,
+        content,
+    }
+}
+
+#[test]
+pub fn signing_domain_prefix_is_version_gated() -> Result<()> {
+    let id = OwnId::generate("https://mypage.com/trust.git".into());
+
+    let old_trust = proof::TrustBuilder::default()
+        .version(-99999)
+        .from(id.id.to_owned())
+        .ids(vec![])
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+    sign_without_domain_prefix(&id, old_trust.clone().into()).verify()?;
+    old_trust.sign_by(&id)?.verify()?;
+
+    let new_trust = proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .ids(vec![])
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+    new_trust.clone().sign_by(&id)?.verify()?;
+    assert!(sign_without_domain_prefix(&id, new_trust.into())
+        .verify()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+pub fn proof_writer_output_parses() -> Result<()> {
+    let mut writer = proof::ProofWriter::new(vec![]);
+    for _ in 0..3 {
+        let (_id, proof) = generate_id_and_proof()?;
+        writer.write_proof(&proof)?;
+    }
+
+    let proofs = Proof::parse(writer.into_inner().as_slice())?;
+    assert_eq!(proofs.len(), 3);
+    for proof in proofs {
+        proof.verify()?;
+    }
+    Ok(())
+}
+
+#[test]
+pub fn signed_parse_expecting_type() -> Result<()> {
+    let s = r#"
+-----BEGIN CODE REVIEW-----
+foo1
+-----BEGIN CODE REVIEW SIGNATURE-----
+sig1
+-----END CODE REVIEW-----
+-----BEGIN CREV TRUST -----
+foo2
+-----BEGIN CREV TRUST SIGNATURE-----
+sig2
+-----END CREV TRUST-----
+"#;
+
+    let err = Serialized::parse_expecting(s.as_bytes(), proof::ProofType::Code).unwrap_err();
+    assert!(err.to_string().contains("Trust"));
+
+    let code_only = s.split("-----BEGIN CREV TRUST").next().unwrap();
+    let proofs = Serialized::parse_expecting(code_only.as_bytes(), proof::ProofType::Code)?;
+    assert_eq!(proofs.len(), 1);
+    Ok(())
+}
+
+#[test]
+pub fn annotated_proof_round_trip() -> Result<()> {
+    let (_id, proof) = generate_id_and_proof()?;
+    let mut annotated = proof::AnnotatedProof::from(proof.clone());
+    annotated.annotations.insert(
+        "imported-from".into(),
+        "https://example.com/peer.git".into(),
+    );
+    annotated
+        .annotations
+        .insert("seen".into(), "2018-12-01".into());
+
+    let (_id, plain) = generate_id_and_proof()?;
+    let s = format!("{}{}", annotated, plain);
+    let parsed = proof::AnnotatedProof::parse(s.as_bytes())?;
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[0].proof.signature, proof.signature);
+    assert_eq!(parsed[0].proof.body, proof.body);
+    assert_eq!(parsed[0].annotations, annotated.annotations);
+    assert!(parsed[1].annotations.is_empty());
+    parsed[0].verify()?;
+    parsed[1].verify()?;
+    Ok(())
+}
+
+#[test]
+pub fn level_unknown_round_trip() -> Result<()> {
+    use crate::Level;
+
+    assert_eq!(serde_yaml::from_str::<Level>("high")?, Level::High);
+    assert_eq!(Level::Low.to_string(), "low");
+
+    let unknown: Level = serde_yaml::from_str("verified")?;
+    assert_eq!(unknown, Level::Unknown("verified".into()));
+    assert!(!unknown.is_known());
+    assert!(unknown < Level::None);
+    assert_eq!(unknown.to_string(), "verified");
+    assert_eq!(
+        serde_yaml::from_str::<Level>(&serde_yaml::to_string(&unknown)?)?,
+        unknown
+    );
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+pub fn export_jsonl_one_line_per_proof() -> Result<()> {
+    let proofs = vec![generate_id_and_proof()?.1, generate_id_and_proof()?.1];
+
+    let mut out = vec![];
+    proof::json::export_jsonl(&proofs, &mut out)?;
+    let out = String::from_utf8(out)?;
+
+    assert_eq!(out.lines().count(), proofs.len());
+    for line in out.lines() {
+        let json: serde_json::Value = serde_json::from_str(line)?;
+        assert_eq!(json["type"], "code");
+    }
+
+    let imported = proof::json::import_jsonl(out.as_bytes())?;
+    assert_eq!(imported.len(), proofs.len());
+    for (imported, orig) in imported.iter().zip(proofs.iter()) {
+        imported.verify()?;
+        assert_eq!(imported.digest, orig.digest);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+pub fn export_jsonl_keeps_co_signatures() -> Result<()> {
+    let (_id, mut proof) = generate_id_and_proof()?;
+    proof.co_sign(&OwnId::generate_for_test())?;
+
+    let mut out = vec![];
+    proof::json::export_jsonl(&[proof.clone()], &mut out)?;
+    let imported = proof::json::import_jsonl(&out[..])?;
+    assert_eq!(imported.len(), 1);
+    assert_eq!(imported[0].co_signatures, proof.co_signatures);
+    imported[0].verify_threshold(2)?;
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+pub fn content_to_json_is_stable() -> Result<()> {
+    let (_id, proof) = generate_id_and_proof()?;
+    assert_eq!(proof.content.to_json()?, proof.content.to_json()?);
+    let reparsed = Proof::parse(proof.to_string().as_bytes())?.remove(0);
+    assert_eq!(reparsed.content.to_json()?, proof.content.to_json()?);
+
+    let trust: proof::Content = proof::TrustBuilder::default()
+        .from(crate::PubId::new(
+            vec![0; 32],
+            "https://example.com/a.git".into(),
+        ))
+        .date(chrono::DateTime::parse_from_rfc3339(
+            "2019-01-01T00:00:00+00:00",
+        )?)
+        .ids(vec![])
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .into();
+    assert_eq!(
+        trust.to_json()?,
+        format!(
+            "{{\"date\":\"2019-01-01T00:00:00+00:00\",\
+             \"from\":{{\"id\":\"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=\",\
+             \"id-type\":\"crev\",\"url\":\"https://example.com/a.git\"}},\
+             \"ids\":[],\"trust\":\"medium\",\"version\":{}}}",
+            crate::current_version()
+        )
+    );
+    Ok(())
+}
+
+#[test]
+pub fn project_review_matches_package() -> Result<()> {
+    let id = OwnId::generate("https://mypage.com/trust.git".into());
+    let review = proof::review::ProjectBuilder::default()
+        .from(id.id.to_owned())
+        .project(generate_project_info("foobar"))
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+
+    assert!(review.matches_package("foo", "0.1.0"));
+    assert!(review.matches_package("FOO", "0.1.0"));
+    assert!(!review.matches_package("foo", "0.1.1"));
+    assert!(!review.matches_package("bar", "0.1.0"));
+    Ok(())
+}
+
+#[test]
+pub fn content_serialized_len() -> Result<()> {
+    let (_id, proof) = generate_id_and_proof()?;
+    assert_eq!(
+        proof.content.serialized_len(),
+        proof.content.to_string().len()
+    );
+    assert_eq!(proof.content.serialized_len(), proof.body.len());
+    Ok(())
+}
+
+#[test]
+pub fn pubid_from_base64() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let key_b64 = id.id.id.to_string();
+
+    let pub_id = crate::PubId::from_base64(&key_b64, id.id.url.as_ref().map(|u| u.url.clone()))?;
+    assert_eq!(pub_id, id.id);
+    assert_eq!(crate::PubId::from_base64(&key_b64, None)?.url, None);
+
+    assert!(crate::PubId::from_base64("not base64!", None).is_err());
+    assert!(crate::PubId::from_base64(&key_b64[..20], None).is_err());
+    assert!(crate::PubId::from_base64("AAAA", None).is_err());
+    Ok(())
+}
+
+#[test]
+pub fn preview_signed_matches_sign_by() -> Result<()> {
+    let (id, proof) = generate_id_and_proof()?;
+
+    let preview = proof.content.preview_signed(&id);
+    preview.verify()?;
+
+    let signed = proof.content.sign_by(&id)?;
+    assert_eq!(preview.body, signed.body);
+    assert_eq!(preview.signature, signed.signature);
+    assert_eq!(proof.content.preview_block(&id), signed.to_string());
+    Ok(())
+}
+
+#[test]
+pub fn signed_parse_invalid_utf8() {
+    let mut s = b"\n-----BEGIN CODE REVIEW-----\nfoo".to_vec();
+    s.extend_from_slice(&[0xff, 0xfe]);
+    s.extend_from_slice(
+        b"\n-----BEGIN CODE REVIEW SIGNATURE-----\nsig\n-----END CODE REVIEW-----\n",
+    );
+
+    let err = Serialized::parse(s.as_slice()).unwrap_err();
+    let err = err
+        .downcast_ref::<proof::InvalidUtf8>()
+        .expect("InvalidUtf8 error");
+    assert_eq!(err.line, 3);
+    assert_eq!(err.offset, 32);
+}
+
+#[test]
+pub fn producer_round_trip() -> Result<()> {
+    let (id, proof) = generate_id_and_proof()?;
+    assert_eq!(proof.content.producer(), None);
+    assert!(!proof.body.contains("producer"));
+
+    let stamped = proof
+        .content
+        .sign_by_with_producer(&id, "cargo-crev 0.5.0")?;
+    stamped.verify()?;
+
+    let parsed = Proof::parse(stamped.to_string().as_bytes())?;
+    assert_eq!(parsed.len(), 1);
+    assert_eq!(parsed[0].content.producer(), Some("cargo-crev 0.5.0"));
+    Ok(())
+}
+
+#[test]
+pub fn content_same_subject() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let review = |version: &str, comment: &str| -> Result<proof::Content> {
+        let mut project = generate_project_info("foobar");
+        project.version = version.into();
+        Ok(proof::review::ProjectBuilder::default()
+            .from(id.id.to_owned())
+            .project(project)
+            .comment(comment.into())
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .into())
+    };
+
+    let older = review("0.1.0", "older")?;
+    let newer = review("0.1.0", "newer")?;
+    let other_version = review("0.2.0", "older")?;
+    assert!(older.same_subject(&newer));
+    assert!(!older.same_subject(&other_version));
+
+    let (_other_id, other_author) = generate_id_and_proof()?;
+    assert!(!older.same_subject(&other_author.content));
+
+    let trust: proof::Content = proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .ids(vec![])
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .into();
+    assert!(!older.same_subject(&trust));
+    assert!(trust.same_subject(&trust.clone()));
+    Ok(())
+}
+
+#[cfg(feature = "tar")]
+#[test]
+pub fn parse_proofs_from_tar() -> Result<()> {
+    let mut builder = tar::Builder::new(vec![]);
+    let mut append = |path: &str, data: &[u8]| -> Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_path(path)?;
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, data)?;
+        Ok(())
+    };
+    let (_id, proof1) = generate_id_and_proof()?;
+    let (_id, proof2) = generate_id_and_proof()?;
+    append("a/one.crev", proof1.to_string().as_bytes())?;
+    append("README.md", b"not a proof")?;
+    append("b/two.crev", proof2.to_string().as_bytes())?;
+    let archive = builder.into_inner()?;
+
+    let proofs = Proof::parse_tar(archive.as_slice())?;
+    assert_eq!(proofs.len(), 2);
+    assert_eq!(proofs[0].0, PathBuf::from("a/one.crev"));
+    assert_eq!(proofs[0].1.digest, proof1.digest);
+    assert_eq!(proofs[1].0, PathBuf::from("b/two.crev"));
+    assert_eq!(proofs[1].1.digest, proof2.digest);
+    Ok(())
+}
+
+#[test]
+pub fn parse_proofs_from_markdown() -> Result<()> {
+    let (_id, proof) = generate_id_and_proof()?;
+    let markdown = format!(
+        "# My review\n\
+         \n\
+         Some prose, with `inline code`.\n\
+         \n\
+         ```rust\n\
+         fn main() {{}}\n\
+         ```\n\
+         \n\
+         ```\n\
+         {}\
+         ```\n\
+         \n\
+         Thanks!\n",
+        proof
+    );
+
+    let parsed = Proof::parse_markdown(&markdown)?;
+    assert_eq!(parsed, vec![proof]);
+    parsed[0].verify()?;
+
+    assert!(Proof::parse_markdown("no fences here\n")?.is_empty());
+    assert!(Proof::parse_markdown("```\nunterminated\n").is_err());
+    Ok(())
+}
+
+#[test]
+pub fn canonical_draft_signs_same_bytes() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let other = OwnId::generate_for_test();
+    let trust: proof::Content = proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .date(chrono::DateTime::parse_from_rfc3339(
+            "2019-01-01T10:00:00+00:00",
+        )?)
+        .ids(vec![other.id.clone()])
+        .trust(crate::Level::High)
+        .comment("met in person".into())
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .into();
+
+    let clean =
+        proof::Content::from_canonical_draft(&trust.to_draft_string(), proof::ProofType::Trust)?;
+    let messy = format!(
+        "# edited by hand\n\
+         comment:   \"met in person\"   \n\
+         trust: high\n\
+         ids:\n\
+         -   id-type: crev\n    \
+             id: {}\n\
+         \n\
+         from: {{ id-type: crev, id: {} }}\n\
+         date: 2019-01-01T10:00:00Z\n",
+        other.id.id, id.id.id
+    );
+    let messy = proof::Content::from_canonical_draft(&messy, proof::ProofType::Trust)?;
+
+    let clean = clean.sign_by(&id)?;
+    let messy = messy.sign_by(&id)?;
+    assert_eq!(clean.body, messy.body);
+    assert_eq!(clean.signature, messy.signature);
+    assert_eq!(clean.body, trust.to_string());
+    messy.verify()?;
+    Ok(())
+}
+
+#[test]
+pub fn content_is_complete() -> Result<()> {
+    let (id, proof) = generate_id_and_proof()?;
+    assert!(proof.content.is_complete());
+
+    let trust: proof::Content = proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .ids(vec![])
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .into();
+    let draft = proof::Content::parse_draft(&trust.to_draft_string(), proof::ProofType::Trust)?;
+    assert!(!draft.is_complete());
+    assert!(draft.validate().is_err());
+
+    let complete = draft.to_draft_string().replace(
+        "ids: []",
+        &format!("ids:\n  - id-type: crev\n    id: {}", id.id.id),
+    );
+    let complete = proof::Content::parse_draft(&complete, proof::ProofType::Trust)?;
+    assert!(complete.is_complete());
+    Ok(())
+}
+
+#[test]
+pub fn pub_id_same_key_ignores_url() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let key = id.id.id.to_string();
+    let moved = crate::PubId::from_base64(&key, Some("https://example.com/moved.git".into()))?;
+    let no_url = crate::PubId::from_base64(&key, None)?;
+
+    assert!(id.id.same_key(&moved));
+    assert!(moved.same_key(&no_url));
+    assert_ne!(id.id, moved);
+    assert_eq!(id.id.id, moved.id);
+
+    let other = OwnId::generate_for_test();
+    assert!(!id.id.same_key(&other.id));
+    assert_ne!(id.id.id, other.id.id);
+    Ok(())
+}
+
+#[test]
+pub fn multi_signature_verify() -> Result<()> {
+    let (_id, mut proof) = generate_id_and_proof()?;
+    let other = OwnId::generate("https://example.com/other.git".into());
+    proof.co_sign(&other)?;
+
+    let parsed = Proof::parse(proof.to_string().as_bytes())?;
+    assert_eq!(parsed.len(), 1);
+    let mut parsed = parsed.into_iter().next().unwrap();
+    assert_eq!(parsed.co_signatures, proof.co_signatures);
+    parsed.verify()?;
+    parsed.verify_threshold(2)?;
+    assert!(parsed.verify_threshold(3).is_err());
+
+    // a repeated signature by the same id is not counted twice
+    parsed.co_signatures.push(parsed.co_signatures[0].clone());
+    assert!(parsed.verify_threshold(3).is_err());
+
+    parsed.co_signatures[0].signature = parsed.signature().to_owned();
+    assert!(parsed.verify().is_err());
+    parsed.verify_threshold(2)?;
+    Ok(())
+}
+
+#[test]
+pub fn verify_with_mock_backend() -> Result<()> {
+    use crate::id::SignatureVerifier;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct Recorder {
+        accept: bool,
+        calls: RefCell<Vec<(Vec<u8>, Vec<u8>, Vec<u8>)>>,
+    }
+
+    impl SignatureVerifier for Recorder {
+        fn verify(&self, pubkey: &[u8], message: &[u8], signature: &[u8]) -> Result<()> {
+            self.calls
+                .borrow_mut()
+                .push((pubkey.to_vec(), message.to_vec(), signature.to_vec()));
+            if !self.accept {
+                bail!("rejected by mock");
+            }
+            Ok(())
+        }
+    }
+
+    let (id, proof) = generate_id_and_proof()?;
+    let accepting = Recorder {
+        accept: true,
+        ..Default::default()
+    };
+    proof.verify_with_backend(&accepting)?;
+    let calls = accepting.calls.into_inner();
+    assert_eq!(calls.len(), 1);
+    let (pubkey, message, signature) = &calls[0];
+    assert_eq!(crate::Id::Crev { id: pubkey.clone() }, id.id.id);
+    assert_eq!(
+        message,
+        &proof::signed_bytes(&proof.body, proof.content.version())
+    );
+    assert_eq!(
+        signature,
+        &base64::decode_config(proof.signature(), base64::URL_SAFE)?
+    );
+
+    assert!(proof.verify_with_backend(&Recorder::default()).is_err());
+    proof.verify()?;
+    Ok(())
+}
+
+#[cfg(feature = "mnemonic")]
+#[test]
+pub fn own_id_mnemonic_round_trip() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let phrase = id.to_mnemonic();
+    assert_eq!(phrase.split_whitespace().count(), 24);
+
+    let recovered = OwnId::from_mnemonic(&phrase, "https://example.com/other.git".into())?;
+    assert_eq!(recovered.id.id, id.id.id);
+    assert_eq!(
+        recovered.keypair.secret.as_bytes(),
+        id.keypair.secret.as_bytes()
+    );
+
+    // all zero entropy, but with a wrong checksum word (should be "art")
+    let wrong_checksum = ["abandon"; 24].join(" ");
+    assert!(OwnId::from_mnemonic(&wrong_checksum, "https://example.com".into()).is_err());
+    let valid = format!("{} art", ["abandon"; 23].join(" "));
+    OwnId::from_mnemonic(&valid, "https://example.com".into())?;
+    Ok(())
+}
+
+#[test]
+pub fn content_verify_digest_length() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let mut project = generate_project_info("foobar");
+    project.digest = vec![0; 32];
+
+    let build = |project: proof::ProjectInfo, digest: Vec<u8>| -> Result<proof::Content> {
+        Ok(proof::review::CodeBuilder::default()
+            .from(id.id.to_owned())
+            .project(project)
+            .files(vec![proof::review::code::File {
+                path: PathBuf::from("foo.x"),
+                digest,
+                digest_type: "sha256".into(),
+            }])
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .into())
+    };
+
+    build(project.clone(), vec![1; 32])?.verify_content_digest()?;
+    assert!(build(project.clone(), vec![1; 31])?
+        .verify_content_digest()
+        .is_err());
+
+    let mut wrong_project = project;
+    wrong_project.digest = vec![0, 1, 2, 3];
+    assert!(build(wrong_project, vec![1; 32])?
+        .verify_content_digest()
+        .is_err());
+    Ok(())
+}
+
+#[test]
+pub fn content_redacted() -> Result<()> {
+    let (_id, proof) = generate_id_and_proof()?;
+
+    let redacted = proof.content.redacted(&proof::RedactOptions {
+        hide_author_url: true,
+        max_comment_len: Some(3),
+    });
+    assert_eq!(redacted.author_url(), None);
+    assert_eq!(redacted.comment(), "com[redacted]");
+
+    // the original is untouched
+    assert!(proof.content.author_url().is_some());
+    assert_eq!(proof.content.comment(), "comment");
+    proof.verify()?;
+
+    let untouched = proof.content.redacted(&Default::default());
+    assert_eq!(untouched.author_url(), proof.content.author_url());
+    assert_eq!(untouched.comment(), "comment");
+    Ok(())
+}
+
+#[cfg(feature = "git")]
+#[test]
+pub fn parse_proofs_from_git_notes() -> Result<()> {
+    let tmp_dir = tempdir::TempDir::new("crev-data-test")?;
+    let repo = git2::Repository::init(tmp_dir.path())?;
+    let sig = git2::Signature::now("crev", "crev@example.com")?;
+    let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+    let first = repo.commit(Some("HEAD"), &sig, &sig, "first", &tree, &[])?;
+    let first_commit = repo.find_commit(first)?;
+    let second = repo.commit(Some("HEAD"), &sig, &sig, "second", &tree, &[&first_commit])?;
+    let second_commit = repo.find_commit(second)?;
+    let third = repo.commit(Some("HEAD"), &sig, &sig, "third", &tree, &[&second_commit])?;
+
+    let (_id, proof1) = generate_id_and_proof()?;
+    let (_id, proof2) = generate_id_and_proof()?;
+    let notes_ref = "refs/notes/crev";
+    repo.note(
+        &sig,
+        &sig,
+        Some(notes_ref),
+        first,
+        &proof1.to_string(),
+        false,
+    )?;
+    repo.note(
+        &sig,
+        &sig,
+        Some(notes_ref),
+        second,
+        &proof2.to_string(),
+        false,
+    )?;
+    repo.note(&sig, &sig, Some(notes_ref), third, "just a note", false)?;
+
+    let mut proofs = Proof::parse_git_notes(tmp_dir.path(), notes_ref)?;
+    proofs.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut expected = vec![
+        (first.to_string(), proof1.digest),
+        (second.to_string(), proof2.digest),
+    ];
+    expected.sort();
+    assert_eq!(
+        proofs
+            .into_iter()
+            .map(|(commit, proof)| (commit, proof.digest))
+            .collect::<Vec<_>>(),
+        expected
+    );
+    Ok(())
+}
+
+#[test]
+pub fn level_u8_conversion() -> Result<()> {
+    use crate::level::OutOfRange;
+    use crate::Level;
+    use std::convert::TryFrom;
+
+    for (v, level) in &[
+        (0, Level::None),
+        (1, Level::Low),
+        (2, Level::Medium),
+        (3, Level::High),
+    ] {
+        assert_eq!(Level::try_from(*v)?, *level);
+        assert_eq!(u8::from(level.clone()), *v);
+    }
+    match Level::try_from(4) {
+        Err(OutOfRange(4)) => {}
+        other => panic!("unexpected: {:?}", other),
+    }
+    assert_eq!(u8::from(Level::Unknown("superb".into())), 0);
+    Ok(())
+}
+
+#[test]
+pub fn sign_by_at_keeps_supplied_date() -> Result<()> {
+    let (id, proof) = generate_id_and_proof()?;
+    let date = chrono::DateTime::parse_from_rfc3339("2018-11-23T10:00:00+01:00")?;
+
+    let resigned = proof.content.sign_by_at(&id, date)?;
+    resigned.verify()?;
+    assert_eq!(*resigned.content.date(), date);
+
+    let parsed = Proof::parse(resigned.to_string().as_bytes())?;
+    assert_eq!(*parsed[0].content.date(), date);
+    parsed[0].verify()?;
+    Ok(())
+}
+
+#[test]
+pub fn expiring_trust_proofs_window() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let now = chrono::Utc::now();
+    let expiring_in = |days: Option<i64>| -> Result<Proof> {
+        proof::TrustBuilder::default()
+            .from(id.id.to_owned())
+            .ids(vec![])
+            .expires(days.map(|days| (now + chrono::Duration::days(days)).into()))
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(&id)
+    };
+
+    let proofs = vec![
+        expiring_in(Some(-1))?,
+        expiring_in(Some(3))?,
+        expiring_in(Some(10))?,
+        expiring_in(Some(60))?,
+        expiring_in(None)?,
+        generate_id_and_proof()?.1,
+    ];
+
+    // the expiration survives serialization
+    let parsed = Proof::parse(proofs[1].to_string().as_bytes())?;
+    parsed[0].verify()?;
+    match parsed[0].content {
+        proof::Content::Trust(ref trust) => {
+            assert!(trust.expires.is_some());
+            assert!(!trust.is_expired_at(now));
+            assert!(trust.is_expired_at(now + chrono::Duration::days(4)));
+        }
+        _ => panic!("expected a trust proof"),
+    }
+
+    let expiring = proof::expiring_trust_proofs(&proofs, chrono::Duration::days(30), now);
+    assert_eq!(expiring, vec![proofs[1].clone(), proofs[2].clone()]);
+    assert!(proof::expiring_trust_proofs(&proofs, chrono::Duration::days(1), now).is_empty());
+    assert!(!proofs[4].body.contains("expires"));
+    Ok(())
+}
+
+#[test]
+pub fn trust_scopes_round_trip() -> Result<()> {
+    use crate::Level;
+
+    let id = OwnId::generate_for_test();
+    let other = OwnId::generate_for_test();
+    let trust_with = |scopes: Vec<(&str, Level)>| -> Result<Proof> {
+        proof::TrustBuilder::default()
+            .from(id.id.to_owned())
+            .ids(vec![other.id.clone()])
+            .trust(Level::Medium)
+            .scopes(
+                scopes
+                    .into_iter()
+                    .map(|(scope, level)| (scope.to_owned(), level))
+                    .collect(),
+            )
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(&id)
+    };
+    let parse_trust = |proof: &Proof| -> Result<proof::Trust> {
+        let parsed = Proof::parse(proof.to_string().as_bytes())?.remove(0);
+        parsed.verify()?;
+        match parsed.content {
+            proof::Content::Trust(trust) => Ok(trust),
+            _ => bail!("expected a trust proof"),
+        }
+    };
+
+    let unscoped = trust_with(vec![])?;
+    assert!(!unscoped.body.contains("scopes"));
+    let trust = parse_trust(&unscoped)?;
+    assert!(trust.scopes.is_empty());
+    assert_eq!(trust.level_for(None), &Level::Medium);
+    assert_eq!(trust.level_for(Some("security")), &Level::Medium);
+
+    let scoped = trust_with(vec![("security", Level::High), ("licensing", Level::Low)])?;
+    let trust = parse_trust(&scoped)?;
+    assert_eq!(trust.scopes.len(), 2);
+    assert_eq!(trust.level_for(Some("security")), &Level::High);
+    assert_eq!(trust.level_for(Some("licensing")), &Level::Low);
+    assert_eq!(trust.level_for(Some("other")), &Level::Medium);
+    assert_eq!(trust.level_for(None), &Level::Medium);
+
+    // scopes can be edited in drafts too
+    let draft = proof::Content::from(trust).to_draft_string();
+    assert!(draft.contains("security: high"));
+    Ok(())
+}
+
+#[test]
+pub fn trust_entries_for_target() -> Result<()> {
+    use crate::Level;
+
+    let target = OwnId::generate_for_test();
+    let other = OwnId::generate_for_test();
+    let trust_by = |truster: &OwnId, ids: Vec<crate::PubId>, level: Level| -> Result<Proof> {
+        proof::TrustBuilder::default()
+            .from(truster.id.to_owned())
+            .ids(ids)
+            .trust(level)
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(truster)
+    };
+
+    let alice = OwnId::generate_for_test();
+    let bob = OwnId::generate_for_test();
+    let carol = OwnId::generate_for_test();
+    let proofs = vec![
+        trust_by(&alice, vec![target.id.clone()], Level::High)?,
+        trust_by(&bob, vec![other.id.clone(), target.id.clone()], Level::Low)?,
+        trust_by(&carol, vec![other.id.clone()], Level::Medium)?,
+        generate_id_and_proof()?.1,
+    ];
+
+    let entries = proof::trust_entries_for(&proofs, &target.id.id);
+    assert_eq!(
+        entries
+            .iter()
+            .map(|(id, level, _date)| (id.clone(), level.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (alice.id.id.clone(), Level::High),
+            (bob.id.id.clone(), Level::Low),
+        ]
+    );
+    assert_eq!(
+        entries[0].2,
+        proofs[0].content.date().with_timezone(&chrono::Utc)
+    );
+    Ok(())
+}
+
+#[cfg(feature = "schema")]
+#[test]
+pub fn schema_json_lists_trust_fields() -> Result<()> {
+    let schema: serde_json::Value = serde_json::from_str(&crate::schema_json())?;
+    let properties = schema["trust"]["properties"]
+        .as_object()
+        .expect("trust schema properties");
+    for field in &[
+        "version", "producer", "date", "ids", "trust", "distrust", "comment",
+    ] {
+        assert!(properties.contains_key(*field), "missing {}", field);
+    }
+    assert!(schema["code"].is_object());
+    assert!(schema["project"].is_object());
+    Ok(())
+}
+
+#[test]
+pub fn verify_with_policy_algorithms() -> Result<()> {
+    let (_id, proof) = generate_id_and_proof()?;
+    proof.verify_with_policy(&Default::default())?;
+
+    let no_sha256 = proof::VerifyPolicy {
+        allowed_digest_algorithms: vec!["blake2b".into()],
+        ..Default::default()
+    };
+    let err = proof.verify_with_policy(&no_sha256).unwrap_err();
+    let err = err
+        .downcast_ref::<proof::DisallowedAlgorithm>()
+        .expect("DisallowedAlgorithm");
+    assert_eq!(err.kind, "digest");
+    assert_eq!(err.algorithm, "sha256");
+
+    let no_signatures = proof::VerifyPolicy {
+        allowed_signature_algorithms: vec![],
+        ..Default::default()
+    };
+    let err = proof.verify_with_policy(&no_signatures).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<proof::DisallowedAlgorithm>()
+            .expect("DisallowedAlgorithm")
+            .kind,
+        "signature"
+    );
+    Ok(())
+}
+
+#[test]
+pub fn proof_summary_format() -> Result<()> {
+    let alice = OwnId::generate_for_test();
+    let bob = OwnId::generate_for_test();
+    let date = chrono::DateTime::parse_from_rfc3339("2019-08-01T10:00:00+00:00")?;
+
+    let trust = proof::TrustBuilder::default()
+        .from(alice.id.to_owned())
+        .date(date)
+        .ids(vec![bob.id.clone()])
+        .trust(crate::Level::High)
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&alice)?;
+    assert_eq!(
+        trust.summary(),
+        format!(
+            "[TRUST]   {}→{} level=high 2019-08-01",
+            alice.id.id, bob.id.id
+        )
+    );
+
+    let code = proof::review::CodeBuilder::default()
+        .from(alice.id.to_owned())
+        .date(date)
+        .project(generate_project_info("foobar"))
+        .files(vec![proof::review::code::File {
+            path: PathBuf::from("foo.x"),
+            digest: vec![1, 2, 3, 4],
+            digest_type: "sha256".into(),
+        }])
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&alice)?;
+    assert_eq!(
+        code.summary(),
+        format!(
+            "[CODE]    {} foo 0.1.0 files=1 trust=medium 2019-08-01",
+            alice.id.id
+        )
+    );
+
+    let project = proof::review::ProjectBuilder::default()
+        .from(alice.id.to_owned())
+        .date(date)
+        .project(generate_project_info("foobar"))
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&alice)?;
+    assert_eq!(
+        project.summary(),
+        format!(
+            "[PROJECT] {} foo 0.1.0 trust=medium 2019-08-01",
+            alice.id.id
+        )
+    );
+    Ok(())
+}
+
+#[test]
+pub fn review_reference_round_trip() -> Result<()> {
+    let (id, proof) = generate_id_and_proof()?;
+    let body = proof.body.clone();
+    let parsed = proof::review::Code::parse(&body)?;
+    assert_eq!(parsed.reference, None);
+    assert!(!body.contains("reference"));
+
+    let review = proof::review::CodeBuilder::default()
+        .from(id.id.to_owned())
+        .project(generate_project_info("foobar"))
+        .files(vec![proof::review::code::File {
+            path: PathBuf::from("foo.x"),
+            digest: vec![1, 2, 3, 4],
+            digest_type: "sha256".into(),
+        }])
+        .reference(Some(crate::Url::new(
+            "https://github.com/dpc/crev/issues/1".into(),
+        )))
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+    let content = proof::Content::from(review.clone());
+    content.validate()?;
+
+    let proof = content.sign_by(&id)?;
+    let parsed = Proof::parse(proof.to_string().as_bytes())?;
+    match parsed[0].content {
+        proof::Content::Code(ref parsed) => assert_eq!(parsed.reference, review.reference),
+        _ => panic!("not a code review"),
+    }
+
+    let draft = proof::Content::parse_draft(&content.to_draft_string(), proof::ProofType::Code)?;
+    match draft {
+        proof::Content::Code(ref draft) => assert_eq!(draft.reference, review.reference),
+        _ => panic!("not a code review"),
+    }
+
+    for bad in &["ftp://example.com/x", "https://", "https://exa mple.com"] {
+        let mut bad_review = review.clone();
+        bad_review.reference = Some(crate::Url::new(bad.to_string()));
+        assert!(proof::Content::from(bad_review).validate().is_err());
+    }
+    Ok(())
+}
+
+#[test]
+pub fn serialized_verify_signature_only() -> Result<()> {
+    let (id, proof) = generate_id_and_proof()?;
+    let other = OwnId::generate_for_test();
+
+    let serialized = Serialized::parse(proof.to_string().as_bytes())?;
+    serialized[0].verify_signature_only(&id.id)?;
+    assert!(serialized[0].verify_signature_only(&other.id).is_err());
+
+    // a body from the future: not a content this version can parse
+    let body = format!(
+        "version: {}\nfrom-future: {}\nsomething-new: [1, 2, 3]\n",
+        crate::current_version(),
+        id.id.id
+    );
+    let signature = id.sign(&proof::signed_bytes(&body, crate::current_version()));
+    let future = Serialized {
+        body,
+        signature: base64::encode_config(&signature, base64::URL_SAFE),
+        type_: proof::ProofType::Trust,
+    };
+    assert!(future.to_parsed().is_err());
+    future.verify_signature_only(&id.id)?;
+    assert!(future.verify_signature_only(&other.id).is_err());
+    Ok(())
+}
+
+#[test]
+pub fn trust_comment_round_trip() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let other = OwnId::generate_for_test();
+    let build = |comment: &str| -> Result<proof::Trust> {
+        proof::TrustBuilder::default()
+            .from(id.id.to_owned())
+            .ids(vec![other.id.clone()])
+            .comment(comment.into())
+            .build()
+            .map_err(|e| format_err!("{}", e))
+    };
+
+    let without = build("")?.sign_by(&id)?;
+    assert!(!without.body.contains("comment"));
+    let parsed = Proof::parse(without.to_string().as_bytes())?;
+    assert_eq!(parsed[0].content.comment(), "");
+
+    let trust = build("verified in person")?;
+    let draft = proof::Content::from(trust.clone()).to_draft_string();
+    assert!(draft.contains("comment: verified in person"));
+    assert!(proof::Content::from(build("")?)
+        .to_draft_string()
+        .contains("comment:"));
+
+    let with = trust.sign_by(&id)?;
+    let mut parsed = Proof::parse(with.to_string().as_bytes())?;
+    assert_eq!(parsed[0].content.comment(), "verified in person");
+    parsed[0].verify()?;
+
+    // the comment is signed
+    parsed[0].body = parsed[0]
+        .body
+        .replace("verified in person", "verified by phone");
+    assert!(parsed[0].verify().is_err());
+    Ok(())
+}
+
+#[test]
+pub fn proof_heap_pops_by_date() -> Result<()> {
+    use std::{cmp::Reverse, collections::BinaryHeap};
+
+    let id = OwnId::generate_for_test();
+    let trust_at = |date: &str| -> Result<Proof> {
+        proof::TrustBuilder::default()
+            .from(id.id.to_owned())
+            .date(chrono::DateTime::parse_from_rfc3339(date)?)
+            .ids(vec![])
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(&id)
+    };
+    let oldest = trust_at("2018-12-01T10:00:00+00:00")?;
+    // earlier in UTC, despite its local time
+    let middle = trust_at("2018-12-02T11:00:00+05:00")?;
+    let newest = trust_at("2018-12-02T10:00:00+00:00")?;
+
+    let mut heap = BinaryHeap::new();
+    for proof in &[middle.clone(), newest.clone(), oldest.clone()] {
+        heap.push(Reverse(proof.clone()));
+    }
+    let popped: Vec<_> = std::iter::from_fn(|| heap.pop().map(|Reverse(proof)| proof)).collect();
+    assert_eq!(popped, vec![oldest.clone(), middle.clone(), newest]);
+
+    let reparsed = Proof::parse(oldest.to_string().as_bytes())?.remove(0);
+    assert_eq!(reparsed, oldest);
+    assert_eq!(reparsed.cmp(&oldest), std::cmp::Ordering::Equal);
+    assert_ne!(oldest, middle);
+    Ok(())
+}
+
+#[test]
+pub fn review_supersedes_chain() -> Result<()> {
+    let (id, first) = generate_id_and_proof()?;
+    assert_eq!(first.content.supersedes(), None);
+    assert!(!first.body.contains("supersedes"));
+
+    let update = |previous: &Proof| -> Result<Proof> {
+        proof::review::CodeBuilder::default()
+            .from(id.id.to_owned())
+            .project(generate_project_info("foobar"))
+            .supersedes(Some(previous.digest.clone()))
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(&id)
+    };
+    let second = update(&first)?;
+    let third = update(&second)?;
+
+    let parsed = Proof::parse(third.to_string().as_bytes())?.remove(0);
+    assert_eq!(parsed.content.supersedes(), Some(second.digest.as_slice()));
+    parsed.verify()?;
+
+    let draft =
+        proof::Content::parse_draft(&parsed.content.to_draft_string(), proof::ProofType::Code)?;
+    assert_eq!(draft.supersedes(), Some(second.digest.as_slice()));
+
+    let proofs = vec![first.clone(), second.clone(), third.clone()];
+    let chain = proof::supersedes_chain(&proofs, &third);
+    assert_eq!(chain, vec![&third, &second, &first]);
+
+    // missing links end the chain
+    let proofs = vec![first, third.clone()];
+    assert_eq!(proof::supersedes_chain(&proofs, &third), vec![&third]);
+    Ok(())
+}
+
+#[test]
+pub fn proof_metrics() -> Result<()> {
+    let s = r#"
+-----BEGIN CREV TRUST -----
+foo
+bar
+-----BEGIN CREV TRUST SIGNATURE-----
+sig
+-----END CREV TRUST-----
+"#;
+    let serialized = Serialized::parse(s.as_bytes())?.remove(0);
+    let proof = Proof {
+        body: serialized.body,
+        signature: serialized.signature,
+        co_signatures: vec![],
+        signature_algorithm: None,
+        digest: vec![],
+        content: generate_id_and_proof()?.1.content,
+    };
+    assert_eq!(
+        proof.metrics(),
+        proof::ProofMetrics {
+            body_len: 8,
+            body_lines: 2,
+            signature_len: 3,
+        }
+    );
+
+    let (_id, proof) = generate_id_and_proof()?;
+    let metrics = proof.metrics();
+    assert_eq!(metrics.body_len, proof.body.len());
+    // base64 of a 64 bytes ed25519 signature
+    assert_eq!(metrics.signature_len, 88);
+    Ok(())
+}
+
+#[test]
+pub fn proof_from_detached() -> Result<()> {
+    let (_id, proof) = generate_id_and_proof()?;
+    let body = proof.body.clone();
+    let signature = format!("{}\n", proof.signature());
+
+    let reassembled = Proof::from_detached(&body, &signature, proof::ProofType::Code)?;
+    reassembled.verify()?;
+    assert_eq!(reassembled.digest, proof.digest);
+    assert_eq!(reassembled.to_string(), proof.to_string());
+
+    let tampered = Proof::from_detached(
+        &body.replace("foo", "bar"),
+        &signature,
+        proof::ProofType::Code,
+    )?;
+    assert!(tampered.verify().is_err());
+    assert!(Proof::from_detached(&body, &signature, proof::ProofType::Trust).is_err());
+    Ok(())
+}
+
+#[test]
+pub fn proof_signature_wrapping() -> Result<()> {
+    let (_id, mut proof) = generate_id_and_proof()?;
+    proof.co_sign(&OwnId::generate_for_test())?;
+
+    let wrapped = proof.to_string_wrapped(Some(64));
+    let signature_lines: Vec<_> = wrapped
+        .lines()
+        .skip_while(|line| *line != "-----BEGIN CODE REVIEW SIGNATURE-----")
+        .skip(1)
+        .take(2)
+        .collect();
+    assert_eq!(signature_lines[0].len(), 64);
+    assert_eq!(
+        format!("{}{}", signature_lines[0], signature_lines[1]),
+        proof.signature()
+    );
+
+    for text in &[wrapped, proof.to_string_wrapped(None)] {
+        let parsed = Proof::parse(text.as_bytes())?.remove(0);
+        assert_eq!(parsed.signature(), proof.signature());
+        assert_eq!(parsed.co_signatures, proof.co_signatures);
+        parsed.verify()?;
+    }
+    assert_eq!(proof.to_string_wrapped(None), proof.to_string());
+    Ok(())
+}
+
+#[test]
+pub fn fingerprint_round_trip() -> Result<()> {
+    use crate::Fingerprint;
+
+    let id = OwnId::generate_for_test();
+    let fingerprint = Fingerprint::from(&id.id);
+    assert_eq!(fingerprint, id.id.id.fingerprint());
+    assert_eq!(fingerprint.as_slice().len(), crate::id::FINGERPRINT_LEN);
+
+    let s = fingerprint.to_string();
+    assert_eq!(s.len(), 2 * crate::id::FINGERPRINT_LEN);
+    assert_eq!(s.parse::<Fingerprint>()?, fingerprint);
+
+    assert_ne!(OwnId::generate_for_test().id.id.fingerprint(), fingerprint);
+    assert!("not hex".parse::<Fingerprint>().is_err());
+    assert!(s[..30].parse::<Fingerprint>().is_err());
+    Ok(())
+}
+
+#[test]
+pub fn verify_trust_path_levels_and_cycles() -> Result<()> {
+    use crate::Level;
+
+    let ids: Vec<_> = (0..5).map(|_| OwnId::generate_for_test()).collect();
+    let trust = |from: usize, to: usize, level: Level| -> Result<Proof> {
+        proof::TrustBuilder::default()
+            .from(ids[from].id.to_owned())
+            .ids(vec![ids[to].id.clone()])
+            .trust(level)
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(&ids[from])
+    };
+    let id = |i: usize| ids[i].id.id.clone();
+
+    // 0 -> 1 -> 2 -> 0 (cycle), 2 -> 3 only at low level, 4 unconnected
+    let proofs = vec![
+        trust(0, 1, Level::High)?,
+        trust(1, 2, Level::Medium)?,
+        trust(2, 0, Level::High)?,
+        trust(2, 3, Level::Low)?,
+    ];
+    let roots = vec![id(0)];
+
+    assert_eq!(
+        proof::verify_trust_path(&proofs, &roots, &id(2), Level::Medium),
+        Some(vec![id(0), id(1), id(2)])
+    );
+    assert_eq!(
+        proof::verify_trust_path(&proofs, &roots, &id(2), Level::High),
+        None
+    );
+    assert_eq!(
+        proof::verify_trust_path(&proofs, &roots, &id(3), Level::Low),
+        Some(vec![id(0), id(1), id(2), id(3)])
+    );
+    assert_eq!(
+        proof::verify_trust_path(&proofs, &roots, &id(3), Level::Medium),
+        None
+    );
+    assert_eq!(
+        proof::verify_trust_path(&proofs, &roots, &id(4), Level::None),
+        None
+    );
+    assert_eq!(
+        proof::verify_trust_path(&proofs, &roots, &id(0), Level::High),
+        Some(vec![id(0)])
+    );
+    Ok(())
+}
+
+#[test]
+pub fn trust_fingerprint_only_target() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let known = OwnId::generate_for_test();
+    let unknown = OwnId::generate_for_test();
+
+    let trust = proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .ids(vec![])
+        .fingerprints(vec![known.id.id.fingerprint()])
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+    let content = proof::Content::from(trust);
+    content.validate()?;
+
+    let proof = content.sign_by(&id)?;
+    let parsed = Proof::parse(proof.to_string().as_bytes())?.remove(0);
+    parsed.verify()?;
+
+    let parsed_trust = match parsed.content {
+        proof::Content::Trust(ref trust) => trust,
+        _ => panic!("not a trust proof"),
+    };
+    assert_eq!(parsed_trust.fingerprints, vec![known.id.id.fingerprint()]);
+    let candidates = vec![unknown.id.clone(), known.id.clone()];
+    assert_eq!(
+        parsed_trust.resolve_fingerprints(&candidates),
+        vec![&known.id]
+    );
+
+    // older proofs, without fingerprints
+    let trust = proof::Trust::parse(&format!(
+        "version: -99999\ndate: \"2018-12-01T10:00:00+00:00\"\nfrom:\n  id-type: crev\n  id: {}\nids: []\ntrust: high\n",
+        id.id.id
+    ))?;
+    assert!(trust.fingerprints.is_empty());
+    Ok(())
+}
+
+#[test]
+pub fn build_trust_edges_verified_drops_tampered() -> Result<()> {
+    use crate::Level;
+
+    let alice = OwnId::generate_for_test();
+    let bob = OwnId::generate_for_test();
+    let target = OwnId::generate_for_test();
+    let trust_by = |truster: &OwnId| -> Result<Proof> {
+        proof::TrustBuilder::default()
+            .from(truster.id.to_owned())
+            .ids(vec![target.id.clone()])
+            .trust(Level::High)
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(truster)
+    };
+
+    let valid = trust_by(&alice)?.to_string();
+    let tampered = trust_by(&bob)?
+        .to_string()
+        .replace("trust: high", "trust: low");
+    let input = format!("{}{}", valid, tampered);
+
+    let (edges, rejected) = proof::build_trust_edges_verified(input.as_bytes())?;
+    assert_eq!(
+        edges
+            .iter()
+            .map(|edge| (edge.from.clone(), edge.to.clone(), edge.level.clone()))
+            .collect::<Vec<_>>(),
+        vec![(alice.id.id.clone(), target.id.id.clone(), Level::High)]
+    );
+    assert_eq!(rejected.len(), 1);
+    Ok(())
+}
+
+#[test]
+pub fn signature_algorithm_default_and_unknown() -> Result<()> {
+    let (_id, proof) = generate_id_and_proof()?;
+    assert_eq!(proof.signature_algorithm, None);
+    assert!(!proof.to_string().contains("sig-alg"));
+
+    let parsed = Proof::parse(proof.to_string().as_bytes())?.remove(0);
+    assert_eq!(parsed.signature_algorithm(), proof::SIGNATURE_ALGORITHM);
+    parsed.verify()?;
+
+    let mut explicit = proof.clone();
+    explicit.signature_algorithm = Some("ed25519".into());
+    let parsed = Proof::parse(explicit.to_string().as_bytes())?.remove(0);
+    assert_eq!(parsed.signature_algorithm, Some("ed25519".into()));
+    parsed.verify()?;
+
+    let mut unknown = proof.clone();
+    unknown.signature_algorithm = Some("rot13".into());
+    let parsed = Proof::parse(unknown.to_string().as_bytes())?.remove(0);
+    let err = parsed.verify().unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<proof::UnsupportedAlgorithm>()
+            .expect("UnsupportedAlgorithm")
+            .algorithm,
+        "rot13"
+    );
+    Ok(())
+}
+
+#[test]
+pub fn content_digest_streaming_matches_signed() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let files = (0..1000)
+        .map(|i| proof::review::code::File {
+            path: PathBuf::from(format!("src/żółw-{}.rs", i)),
+            digest: vec![1, 2, 3, 4],
+            digest_type: "sha256".into(),
+        })
+        .collect();
+    let content = proof::Content::from(
+        proof::review::CodeBuilder::default()
+            .from(id.id.to_owned())
+            .project(generate_project_info("foobar"))
+            .comment("Dawid Ciężarkiewicz ".repeat(100))
+            .files(files)
+            .build()
+            .map_err(|e| format_err!("{}", e))?,
+    );
+
+    let proof = content.preview_signed(&id);
+    let mut tee = vec![];
+    assert_eq!(content.digest_streaming(Some(&mut tee))?, proof.digest);
+    assert_eq!(String::from_utf8(tee)?, proof.body);
+    assert_eq!(content.digest_streaming(None)?, proof.digest);
+    Ok(())
+}
+
+#[test]
+pub fn headerless_yaml_matches_to_string_output() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let review = proof::review::CodeBuilder::default()
+        .from(id.id.to_owned())
+        .project(generate_project_info("foobar"))
+        .comment("Dawid Ciężarkiewicz\nsecond line ".repeat(100))
+        .files(
+            (0..100)
+                .map(|i| proof::review::code::File {
+                    path: PathBuf::from(format!("src/żółw-{}.rs", i)),
+                    digest: vec![1, 2, 3, 4],
+                    digest_type: "sha256".into(),
+                })
+                .collect(),
+        )
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+
+    // what `write_as_headerless_yaml` used to do, before streaming
+    let document = serde_yaml::to_string(&review)?;
+    let mut lines = document.lines();
+    assert_eq!(lines.next(), Some("---"));
+    let expected: String = lines.map(|line| format!("{}\n", line)).collect();
+
+    assert_eq!(review.to_string(), expected);
+    let content = proof::Content::from(review);
+    assert_eq!(
+        content.digest_streaming(None)?,
+        crev_common::blake2sum(expected.as_bytes())
+    );
+    Ok(())
+}
+
+#[test]
+pub fn verify_pinned_ignore_url() -> Result<()> {
+    let (id, proof) = generate_id_and_proof()?;
+    let mut moved = id.id.clone();
+    moved.set_git_url("https://example.com/moved/trust.git".into());
+
+    let policy = proof::VerifyPolicy::default();
+    proof.verify_pinned(&id.id, &policy)?;
+    assert!(proof.verify_pinned(&moved, &policy).is_err());
+
+    let ignore_url = proof::VerifyPolicy {
+        ignore_url: true,
+        ..Default::default()
+    };
+    proof.verify_pinned(&moved, &ignore_url)?;
+    assert!(proof
+        .verify_pinned(&OwnId::generate_for_test().id, &ignore_url)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+pub fn distinct_authors_dedups_and_sorts() -> Result<()> {
+    let ids: Vec<_> = (0..3).map(|_| OwnId::generate_for_test()).collect();
+    let trust = |from: &OwnId| -> Result<Proof> {
+        proof::TrustBuilder::default()
+            .from(from.id.to_owned())
+            .ids(vec![ids[0].id.clone()])
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .sign_by(from)
+    };
+    let proofs = vec![
+        trust(&ids[2])?,
+        trust(&ids[0])?,
+        trust(&ids[2])?,
+        trust(&ids[1])?,
+        trust(&ids[0])?,
+    ];
+
+    let mut expected: Vec<_> = ids.iter().map(|id| id.id.id.clone()).collect();
+    expected.sort_by_key(crate::Id::fingerprint);
+    assert_eq!(proof::distinct_authors(&proofs), expected);
+    assert!(proof::distinct_authors(&[]).is_empty());
+    Ok(())
+}
+
+#[test]
+pub fn content_template_round_trips_through_draft() -> Result<()> {
+    use crate::proof::ProofType;
+
+    let id = OwnId::generate_for_test();
+    let trusted = OwnId::generate_for_test();
+    let input = proof::TemplateInput {
+        from: id.id.clone(),
+        project: Some(generate_project_info("foobar")),
+        files: vec![],
+        ids: vec![trusted.id.clone()],
+    };
+
+    for &type_ in &[ProofType::Code, ProofType::Project, ProofType::Trust] {
+        let content = proof::Content::template(type_, input.clone())?;
+        assert_eq!(content.proof_type(), type_);
+        assert_eq!(content.comment(), "");
+        assert_eq!(content.author_id(), id.id.id);
+
+        let parsed = proof::Content::parse_draft(&content.to_draft_string(), type_)?;
+        assert_eq!(parsed.to_string(), content.to_string());
+    }
+
+    let no_project = proof::TemplateInput {
+        project: None,
+        ..input
+    };
+    assert!(proof::Content::template(ProofType::Code, no_project.clone()).is_err());
+    proof::Content::template(ProofType::Trust, no_project)?;
+    Ok(())
+}
+
+#[test]
+pub fn verify_stream_budgeted_stops_at_budget() -> Result<()> {
+    let mut input = String::new();
+    for _ in 0..4 {
+        input += &generate_id_and_proof()?.1.to_string();
+    }
+    input += &generate_id_and_proof()?
+        .1
+        .to_string()
+        .replace("comment: comment", "comment: tampered");
+
+    let (results, exhausted) = proof::verify_stream_budgeted(input.as_bytes(), 2);
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(Result::is_ok));
+    assert!(exhausted);
+
+    let (results, exhausted) = proof::verify_stream_budgeted(input.as_bytes(), 5);
+    assert_eq!(results.len(), 5);
+    assert!(results[..4].iter().all(Result::is_ok));
+    assert!(results[4].is_err());
+    assert!(!exhausted);
+
+    let (results, exhausted) = proof::verify_stream_budgeted(input.as_bytes(), 10);
+    assert_eq!(results.len(), 5);
+    assert!(!exhausted);
+    Ok(())
+}
+
+#[test]
+pub fn empty_trust_parses_but_never_verifies() -> Result<()> {
+    let empty = Proof::empty_trust();
+    assert!(empty.is_placeholder());
+    assert!(empty.verify().is_err());
+    assert_eq!(empty.content.kind_str(), "trust");
+
+    let parsed = Proof::parse(empty.to_string().as_bytes())?.remove(0);
+    assert_eq!(parsed, empty);
+    assert!(parsed.is_placeholder());
+    assert!(parsed.verify().is_err());
+
+    assert_eq!(Proof::empty_trust().to_string(), empty.to_string());
+    assert!(!generate_id_and_proof()?.1.is_placeholder());
+    Ok(())
+}
+
+#[test]
+pub fn verify_cached_skips_known_proofs() -> Result<()> {
+    use crate::id::SignatureVerifier;
+    use std::cell::Cell;
+
+    #[derive(Default)]
+    struct Counter {
+        calls: Cell<usize>,
+    }
+
+    impl SignatureVerifier for Counter {
+        fn verify(&self, pubkey: &[u8], message: &[u8], signature: &[u8]) -> Result<()> {
+            self.calls.set(self.calls.get() + 1);
+            crate::id::Ed25519Verifier.verify(pubkey, message, signature)
+        }
+    }
+
+    let (_id, proof) = generate_id_and_proof()?;
+    let counter = Counter::default();
+    let mut cache = proof::VerifyCache::new();
+
+    proof.verify_cached_with_backend(&mut cache, &counter)?;
+    assert_eq!(counter.calls.get(), 1);
+    assert_eq!(cache.len(), 1);
+    proof.verify_cached_with_backend(&mut cache, &counter)?;
+    assert_eq!(counter.calls.get(), 1);
+
+    // same body, different signature: not in the cache
+    let mut forged = proof.clone();
+    forged.signature = generate_id_and_proof()?.1.signature;
+    assert!(forged
+        .verify_cached_with_backend(&mut cache, &counter)
+        .is_err());
+    assert_eq!(counter.calls.get(), 2);
+    assert_eq!(cache.len(), 1);
+    Ok(())
+}
+
+#[test]
+pub fn with_self_trust_adds_root_self_edges() -> Result<()> {
+    use crate::Level;
+
+    let ids: Vec<_> = (0..3).map(|_| OwnId::generate_for_test().id.id).collect();
+    let edge = |from: usize, to: usize, level: Level| proof::TrustEdge {
+        from: ids[from].clone(),
+        to: ids[to].clone(),
+        level,
+        date: chrono::Utc::now(),
+    };
+    let edges = vec![edge(0, 1, Level::Medium), edge(1, 1, Level::Low)];
+    let roots = vec![ids[0].clone(), ids[1].clone(), ids[0].clone()];
+
+    let res = proof::with_self_trust(&edges, &roots);
+    let self_edges: Vec<_> = res.iter().filter(|edge| edge.from == edge.to).collect();
+    assert_eq!(self_edges.len(), 2);
+    for root in &roots {
+        let self_edge = self_edges
+            .iter()
+            .find(|edge| edge.from == *root)
+            .expect("self-edge of a root");
+        assert_eq!(self_edge.level, Level::High);
+    }
+    assert!(res.contains(&edges[0]));
+    assert!(!res.iter().any(|edge| edge.from == ids[2]));
+    Ok(())
+}
+
+#[test]
+pub fn proof_abbreviated_signature() -> Result<()> {
+    let (_id, proof) = generate_id_and_proof()?;
+    let signature = proof.signature();
+    let abbrev = proof.to_string_abbrev_sig(8);
+
+    assert!(abbrev.contains(&format!("\n{}…\n", &signature[..8])));
+    assert!(!abbrev.contains(&signature[..9]));
+    assert!(abbrev.contains(&proof.body));
+    assert_eq!(
+        abbrev.replace(&format!("{}…", &signature[..8]), signature),
+        proof.to_string()
+    );
+    Ok(())
+}
+
+#[test]
+pub fn signed_parse_rejects_huge_line() -> Result<()> {
+    use std::io::Read;
+
+    // a reader that would produce a line way longer than any limit
+    let huge_line = std::io::repeat(b'a').take(100 * 1024 * 1024);
+    let begin: &[u8] = b"-----BEGIN CREV TRUST -----\n";
+    let input = begin.chain(huge_line).chain(&b"\n"[..]);
+    let err = Proof::parse(std::io::BufReader::new(input)).unwrap_err();
+    assert!(err.to_string().contains("Line 2 too long"));
+
+    let limits = proof::ParseLimits {
+        max_line_len: 40,
+        ..Default::default()
+    };
+    let block = |body_line: &str| {
+        format!(
+            "-----BEGIN CREV TRUST -----\r\n{}\r\n-----BEGIN CREV TRUST SIGNATURE-----\nsig\n-----END CREV TRUST-----\n",
+            body_line
+        )
+    };
+    let line = "a".repeat(40);
+    assert_eq!(
+        Serialized::parse_with_limits(block(&line).as_bytes(), limits)?[0].body,
+        format!("{}\n", line)
+    );
+    let err =
+        Serialized::parse_with_limits(block(&format!("{}a", line)).as_bytes(), limits).unwrap_err();
+    assert!(err.to_string().contains("Line 2 too long"));
+    // carriage returns don't hide the rest of a too long line
+    let err = Serialized::parse_with_limits(block(&format!("{}\r\rb", line)).as_bytes(), limits)
+        .unwrap_err();
+    assert!(err.to_string().contains("Line 2 too long"));
+    Ok(())
+}
+
+#[test]
+pub fn signed_parse_strips_line_endings_like_lines() -> Result<()> {
+    use std::io::BufRead;
+
+    let input = "-----BEGIN CREV TRUST -----\r\na\r\r\nb\rc\n\r\n-----BEGIN CREV TRUST SIGNATURE-----\nsig\n-----END CREV TRUST-----";
+    let expected: String = input
+        .as_bytes()
+        .lines()
+        .skip(1)
+        .take(3)
+        .map(|line| line.map(|line| line + "\n"))
+        .collect::<std::io::Result<_>>()?;
+    assert_eq!(expected, "a\r\nb\rc\n\n");
+    assert_eq!(Serialized::parse(input.as_bytes())?[0].body, expected);
+    Ok(())
+}
+
+#[test]
+pub fn parse_only_skips_other_types() -> Result<()> {
+    use crate::proof::ProofType;
+
+    let (id, review) = generate_id_and_proof()?;
+    let trust = proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .ids(vec![OwnId::generate_for_test().id])
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&id)?;
+    // content of a skipped proof isn't even looked at
+    let broken_review = "-----BEGIN CODE REVIEW-----\nnot: [a review\n-----BEGIN CODE REVIEW SIGNATURE-----\nsig\n-----END CODE REVIEW-----\n";
+    let input = format!("{}{}{}{}", review, broken_review, trust, trust);
+
+    let proofs = Proof::parse_only(input.as_bytes(), ProofType::Trust)?;
+    assert_eq!(proofs.len(), 2);
+    assert!(proofs.iter().all(|proof| proof == &trust));
+    assert!(Proof::parse(input.as_bytes()).is_err());
+    assert!(Proof::parse_only(input.as_bytes(), ProofType::Code).is_err());
+    Ok(())
+}
+
+#[test]
+pub fn verify_full_reports_warnings() -> Result<()> {
+    use crate::proof::{ContentCommon, ProofType, Warning};
+
+    let id = OwnId::generate_for_test();
+    let expires = chrono::Utc::now() + chrono::Duration::days(2);
+    let trust = proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .ids(vec![OwnId::generate_for_test().id])
+        .expires(Some(expires.into()))
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+    let policy = proof::VerifyPolicy::default();
+
+    let proof = trust.clone().sign_by(&id)?;
+    let outcome = proof.verify_full(&policy)?;
+    assert_eq!(outcome.content.to_string(), proof.body);
+    assert_eq!(
+        outcome.warnings,
+        vec![Warning::TrustExpiresSoon(trust.expires.unwrap())]
+    );
+
+    // signed by some future version, with a field unknown to this one
+    let body = format!("{}future-field: 1\n", trust);
+    let signature = id.sign(&proof::signed_bytes(&body, trust.version()));
+    let proof = Proof::from_detached(
+        &body,
+        &base64::encode_config(&signature, base64::URL_SAFE),
+        ProofType::Trust,
+    )?;
+    let outcome = proof.verify_full(&proof::VerifyPolicy {
+        expiry_warning: chrono::Duration::days(1),
+        ..Default::default()
+    })?;
+    assert_eq!(
+        outcome.warnings,
+        vec![Warning::UnknownField("future-field".into())]
+    );
+
+    let mut tampered = proof.clone();
+    tampered.body = tampered.body.replace("future-field: 1", "future-field: 2");
+    assert!(tampered.verify_full(&policy).is_err());
+    Ok(())
+}
+
+#[test]
+pub fn verify_full_knows_all_fields() -> Result<()> {
+    use crate::Level;
+
+    let id = OwnId::generate_for_test();
+    let reference = Some(crate::Url::new(
+        "https://github.com/dpc/crev/issues/1".into(),
+    ));
+    let trust = proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .producer(Some("cargo-crev 0.5.0".into()))
+        .ids(vec![OwnId::generate_for_test().id])
+        .fingerprints(vec![OwnId::generate_for_test().id.id.fingerprint()])
+        .scopes(
+            vec![("security".to_owned(), Level::High)]
+                .into_iter()
+                .collect(),
+        )
+        .distrust(Level::High)
+        .expires(Some(
+            (chrono::Utc::now() + chrono::Duration::days(365)).into(),
+        ))
+        .comment("all fields".into())
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+    let code = proof::review::CodeBuilder::default()
+        .from(id.id.to_owned())
+        .producer(Some("cargo-crev 0.5.0".into()))
+        .project(generate_project_info("foobar"))
+        .comment("all fields".into())
+        .reference(reference.clone())
+        .supersedes(Some(vec![1, 2, 3]))
+        .files(vec![proof::review::code::File {
+            path: PathBuf::from("foo.x"),
+            digest: vec![1, 2, 3, 4],
+            digest_type: "sha256".into(),
+        }])
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+    let project = proof::review::ProjectBuilder::default()
+        .from(id.id.to_owned())
+        .producer(Some("cargo-crev 0.5.0".into()))
+        .project(generate_project_info("foobar"))
+        .comment("all fields".into())
+        .reference(reference)
+        .supersedes(Some(vec![1, 2, 3]))
+        .build()
+        .map_err(|e| format_err!("{}", e))?;
+
+    for content in vec![proof::Content::from(trust), code.into(), project.into()] {
+        let proof = content.sign_by(&id)?;
+        let outcome = proof.verify_full(&proof::VerifyPolicy::default())?;
+        assert!(outcome.warnings.is_empty(), "{:?}", outcome.warnings);
+        assert!(!proof.requires_newer_client());
+    }
+    Ok(())
+}
+
+#[test]
+pub fn level_combinators_saturate() -> Result<()> {
+    use crate::Level;
+
+    assert_eq!(Level::Low.min(Level::High), Level::Low);
+    assert_eq!(Level::High.min(Level::Low), Level::Low);
+    assert_eq!(Level::Low.max(Level::High), Level::High);
+    assert_eq!(Level::None.max(Level::None), Level::None);
+    let unknown = Level::Unknown("superb".into());
+    assert_eq!(unknown.clone().min(Level::None), unknown);
+    assert_eq!(unknown.clone().max(Level::None), Level::None);
+
+    assert_eq!(Level::None.step_up(), Level::Low);
+    assert_eq!(Level::Medium.step_up(), Level::High);
+    assert_eq!(Level::High.step_up(), Level::High);
+    assert_eq!(Level::High.step_down(), Level::Medium);
+    assert_eq!(Level::Low.step_down(), Level::None);
+    assert_eq!(Level::None.step_down(), Level::None);
+    assert_eq!(unknown.clone().step_up(), unknown);
+    assert_eq!(unknown.clone().step_down(), unknown);
+    Ok(())
+}
+
+#[test]
+pub fn transparency_log_inclusion() -> Result<()> {
+    let proofs = (0..7)
+        .map(|_| Ok(generate_id_and_proof()?.1))
+        .collect::<Result<Vec<_>>>()?;
+    let leaves: Vec<_> = proofs.iter().map(|proof| proof.digest.clone()).collect();
+    let root = proof::merkle_root(&leaves);
+
+    for (i, proof) in proofs.iter().enumerate() {
+        let mut annotated = proof::AnnotatedProof::from(proof.clone());
+        assert!(annotated.inclusion_proof()?.is_none());
+        assert!(annotated.verify_inclusion(&root).is_err());
+
+        let inclusion = proof::InclusionProof::build(&leaves, i).expect("in range");
+        annotated.set_inclusion_proof(&inclusion);
+        let parsed = proof::AnnotatedProof::parse(annotated.to_string().as_bytes())?.remove(0);
+        assert_eq!(parsed.inclusion_proof()?, Some(inclusion.clone()));
+        parsed.verify_inclusion(&root)?;
+
+        // wrong root, wrong position, and tampered path
+        assert!(parsed
+            .verify_inclusion(&proof::merkle_root(&leaves[..6]))
+            .is_err());
+        let mut moved = inclusion.clone();
+        moved.index = (moved.index + 1) % 7;
+        assert!(moved.verify(&proof.digest, &root).is_err());
+        let mut tampered = inclusion;
+        tampered.path[0][0] ^= 1;
+        assert!(tampered.verify(&proof.digest, &root).is_err());
+    }
+    assert!(proof::InclusionProof::build(&leaves, 7).is_none());
+    Ok(())
+}
+
+#[test]
+pub fn expiration_and_co_signatures_are_version_gated() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let trust_at = |version: i64, expires: bool| -> Result<proof::Trust> {
+        proof::TrustBuilder::default()
+            .version(version)
+            .from(id.id.to_owned())
+            .ids(vec![])
+            .expires(if expires {
+                Some(chrono::Utc::now().into())
+            } else {
+                None
+            })
+            .build()
+            .map_err(|e| format_err!("{}", e))
+    };
+
+    let expiring = trust_at(crate::current_version(), true)?.sign_by(&id)?;
+    Proof::parse(expiring.to_string().as_bytes())?;
+    assert!(trust_at(-99999, true)?.sign_by(&id).is_err());
+    let old_expiring = expiring.body.replace(
+        &format!("version: {}", crate::current_version()),
+        "version: -99999",
+    );
+    assert!(proof::Trust::parse(&old_expiring).is_err());
+
+    let mut co_signed = trust_at(crate::current_version(), false)?.sign_by(&id)?;
+    co_signed.co_sign(&OwnId::generate_for_test())?;
+    co_signed.verify_threshold(2)?;
+    Proof::parse(co_signed.to_string().as_bytes())?[0].verify()?;
+
+    let mut old = trust_at(-99999, false)?.sign_by(&id)?;
+    assert!(old.co_sign(&OwnId::generate_for_test()).is_err());
+    // co-signatures added by hand are rejected too
+    old.co_signatures = co_signed.co_signatures.clone();
+    assert!(old.verify().is_err());
+    assert!(old.verify_threshold(1).is_err());
+    assert!(Proof::parse(old.to_string().as_bytes()).is_err());
+    old.co_signatures.clear();
+    old.verify()?;
+    Ok(())
+}
+
+#[test]
+pub fn sign_with_fixed_clock_is_reproducible() -> Result<()> {
+    use crev_common::FixedClock;
+
+    let id = OwnId::generate_for_test();
+    let clock = FixedClock(chrono::DateTime::parse_from_rfc3339(
+        "2019-01-02T03:04:05+06:00",
+    )?);
+    let input = proof::TemplateInput {
+        from: id.id.to_owned(),
+        project: None,
+        files: vec![],
+        ids: vec![],
+    };
+    let content = proof::Content::template_with_clock(proof::ProofType::Trust, input, &clock)?;
+    assert_eq!(*content.date(), clock.0);
+
+    let proof = content.sign_by_with_clock(&id, &clock)?;
+    proof.verify()?;
+    let parsed = Proof::parse(proof.to_string().as_bytes())?;
+    assert_eq!(
+        parsed[0].content.date().to_rfc3339(),
+        "2019-01-02T03:04:05+06:00"
+    );
+    let again = content.sign_by_with_clock(&id, &clock)?;
+    assert_eq!(proof.to_string(), again.to_string());
+    Ok(())
+}
+
+#[test]
+pub fn project_info_alt_digests() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let mut project = generate_project_info("foobar");
+    project.digest = vec![1; 32];
+    project.alt_digests.insert("sha256".into(), vec![2; 32]);
+
+    let proof = proof::review::CodeBuilder::default()
+        .from(id.id.to_owned())
+        .project(project.clone())
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&id)?;
+    assert!(proof.body.contains("alt-digests:"));
+
+    let parsed = Proof::parse(proof.to_string().as_bytes())?;
+    parsed[0].verify()?;
+    let parsed_project = match parsed[0].content {
+        proof::Content::Code(ref review) => review.project.clone(),
+        _ => bail!("Wrong proof type"),
+    };
+    assert_eq!(parsed_project, project);
+    assert!(project.matches_digest("blake2b", &[1; 32]));
+    assert!(project.matches_digest("sha256", &[2; 32]));
+    assert!(!project.matches_digest("sha256", &[1; 32]));
+    assert!(!project.matches_digest("md5", &[1; 32]));
+
+    // proofs with a single digest don't mention the others at all
+    let (_id, single) = generate_id_and_proof()?;
+    assert!(!single.body.contains("alt-digests"));
+    let parsed = Proof::parse(single.to_string().as_bytes())?;
+    match parsed[0].content {
+        proof::Content::Code(ref review) => {
+            assert!(review.project.alt_digests.is_empty());
+            assert_eq!(review.project.digests().count(), 1);
+        }
+        _ => bail!("Wrong proof type"),
+    }
+    Ok(())
+}
+
+#[test]
+pub fn is_signed_by_tells_authors_apart() -> Result<()> {
+    let (alice, alice_proof) = generate_id_and_proof()?;
+    let bob = OwnId::generate_for_test();
+    let bob_proof = proof::TrustBuilder::default()
+        .from(bob.id.to_owned())
+        .ids(vec![alice.id.to_owned()])
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&bob)?;
+
+    assert!(alice_proof.is_signed_by(&alice));
+    assert!(!alice_proof.is_signed_by(&bob));
+    assert!(bob_proof.is_signed_by(&bob));
+    assert!(!bob_proof.is_signed_by(&alice));
+
+    let mine: Vec<_> = [alice_proof, bob_proof]
+        .iter()
+        .filter(|proof| proof.is_signed_by(&bob))
+        .cloned()
+        .collect();
+    assert_eq!(mine.len(), 1);
+    assert_eq!(mine[0].content.author_id(), bob.id.id);
+    Ok(())
+}
+
+#[test]
+pub fn project_info_canonical_form() -> Result<()> {
+    let project = generate_project_info("foobar");
+    let canonical = "\
+source: \"https://crates.io\"
+name: foo
+version: 0.1.0
+revision: foobar
+digest: AAECAw==
+";
+    assert_eq!(project.to_string(), canonical);
+    assert_eq!(proof::ProjectInfo::parse(canonical)?, project);
+
+    let mut full = project.clone();
+    full.id = Some(OwnId::generate_for_test().id);
+    full.revision_type = "hg".into();
+    full.digest_type = "sha256".into();
+    full.alt_digests.insert("blake2b".into(), vec![4, 5, 6, 7]);
+    let parsed = proof::ProjectInfo::parse(&full.to_string())?;
+    assert_eq!(parsed, full);
+    assert_eq!(parsed.to_string(), full.to_string());
+
+    assert!(proof::ProjectInfo::parse("name: foo").is_err());
+    Ok(())
+}
+
+#[test]
+pub fn target_digest_per_proof_type() -> Result<()> {
+    let (id, code_proof) = generate_id_and_proof()?;
+    assert_eq!(code_proof.content.target_digest(), Some(&[0, 1, 2, 3][..]));
+
+    let mut project = generate_project_info("foobar");
+    project.digest = vec![9; 32];
+    let project_review: proof::Content = proof::review::ProjectBuilder::default()
+        .from(id.id.to_owned())
+        .project(project)
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .into();
+    assert_eq!(project_review.target_digest(), Some(&[9; 32][..]));
+
+    let trust: proof::Content = proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .ids(vec![])
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .into();
+    assert_eq!(trust.target_digest(), None);
+    Ok(())
+}
+
+#[test]
+pub fn offline_signing_then_timestamping() -> Result<()> {
+    use crate::proof::{AnnotatedProof, Timestamp};
+
+    // offline: the content has a fixed date and only the key signs
+    let author = OwnId::generate_for_test();
+    let date = chrono::DateTime::parse_from_rfc3339("2019-03-01T12:00:00+01:00")?;
+    let content: proof::Content = proof::TrustBuilder::default()
+        .from(author.id.to_owned())
+        .ids(vec![OwnId::generate_for_test().id])
+        .date(date)
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .into();
+    let signing_bytes = content.signing_bytes();
+    let signature = author.sign(&signing_bytes);
+
+    // back online: assemble, transfer, timestamp
+    let proof = content.attach_signature(&signature)?;
+    assert_eq!(proof.to_string(), content.sign_by(&author)?.to_string());
+    assert!(content
+        .attach_signature(&OwnId::generate_for_test().sign(&signing_bytes))
+        .is_err());
+    let transferred = Proof::parse(proof.to_string().as_bytes())?.remove(0);
+
+    let authority = OwnId::generate_for_test();
+    let stamp_date = chrono::DateTime::parse_from_rfc3339("2019-03-05T08:00:00+00:00")?;
+    let timestamp = Timestamp::create(&transferred, stamp_date, &authority);
+    let mut annotated = AnnotatedProof::from(transferred);
+    assert!(annotated.verify_timestamped().is_err());
+    annotated.attach_timestamp(&timestamp)?;
+
+    let stored = AnnotatedProof::parse(annotated.to_string().as_bytes())?.remove(0);
+    let verified = stored.verify_timestamped()?;
+    assert_eq!(verified, timestamp);
+    assert_eq!(verified.by, authority.id.id);
+    assert_eq!(*stored.proof.content.date(), date);
+
+    // a timestamp of another proof doesn't attach
+    let (_id, other) = generate_id_and_proof()?;
+    let mut wrong = AnnotatedProof::from(other);
+    assert!(wrong.attach_timestamp(&timestamp).is_err());
+    Ok(())
+}
+
+#[test]
+pub fn parse_stdin_contract() -> Result<()> {
+    use std::io::Cursor;
+
+    // what `Proof::parse_stdin` does with these inputs
+    let stdin = |s: &str| Proof::parse(Cursor::new(s.as_bytes().to_vec()));
+
+    assert!(stdin("")?.is_empty());
+    assert!(stdin("\n  \n")?.is_empty());
+
+    let (_id, proof) = generate_id_and_proof()?;
+    let full = proof.to_string();
+    assert_eq!(stdin(&full)?.len(), 1);
+
+    let closed_early = &full[..full.find("SIGNATURE-----").expect("signature block")];
+    let err = stdin(closed_early).unwrap_err();
+    assert!(err.to_string().contains("Unexpected EOF"));
+    Ok(())
+}
+
+#[test]
+pub fn detect_proof_types_of_files() -> Result<()> {
+    use crate::proof::ProofType;
+
+    let (id, code) = generate_id_and_proof()?;
+    let project = proof::review::ProjectBuilder::default()
+        .from(id.id.to_owned())
+        .project(generate_project_info("foobar"))
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&id)?;
+    let trust = proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .ids(vec![])
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&id)?;
+
+    let mixed = format!("{}{}{}{}", trust, code, project, trust);
+    let types = proof::detect_proof_types(mixed.as_bytes())?;
+    assert_eq!(
+        types,
+        vec![ProofType::Code, ProofType::Project, ProofType::Trust]
+            .into_iter()
+            .collect()
+    );
+
+    let only_trust = format!("{}{}", trust, trust);
+    let types = proof::detect_proof_types(only_trust.as_bytes())?;
+    assert_eq!(types, Some(ProofType::Trust).into_iter().collect());
+
+    assert!(proof::detect_proof_types(&b""[..])?.is_empty());
+    Ok(())
+}
+
+#[test]
+pub fn parse_limits_total_body_bytes() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let trust = proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .ids(vec![])
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&id)?;
+    let body_len = trust.body.len();
+    let file: String = (0..10).map(|_| trust.to_string()).collect();
+
+    let limits = |max_total_body_bytes| proof::ParseLimits {
+        max_total_body_bytes,
+        ..Default::default()
+    };
+    assert_eq!(
+        Proof::parse_with_limits(file.as_bytes(), limits(10 * body_len))?.len(),
+        10
+    );
+    let err = Proof::parse_with_limits(file.as_bytes(), limits(10 * body_len - 1)).unwrap_err();
+    assert!(err.to_string().contains("in total"));
+    // each proof alone is well under the per-proof limit
+    assert!(body_len < proof::ParseLimits::default().max_body_len);
+    assert_eq!(Proof::parse(file.as_bytes())?.len(), 10);
+    Ok(())
+}
+
+#[test]
+pub fn requires_newer_client_for_future_proofs() -> Result<()> {
+    use crate::proof::ProofType;
+
+    let id = OwnId::generate_for_test();
+    let current = proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .ids(vec![])
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(&id)?;
+    assert!(!current.requires_newer_client());
+
+    let signed = |body: String| -> Result<Proof> {
+        let signature = id.sign(&proof::signed_bytes(&body, crate::current_version()));
+        Proof::from_detached(
+            &body,
+            &base64::encode_config(&signature, base64::URL_SAFE),
+            ProofType::Trust,
+        )
+    };
+    let current_version = format!("version: {}\n", crate::current_version());
+    assert!(current.body.starts_with(&current_version));
+
+    let future = signed(current.body.replacen(&current_version, "version: 1\n", 1))?;
+    assert_eq!(future.content.version(), 1);
+    assert!(future.requires_newer_client());
+
+    let future_field = signed(format!("{}future-field: 1\n", current.body))?;
+    assert!(future_field.requires_newer_client());
+
+    let mut future_algorithm = current.clone();
+    future_algorithm.signature_algorithm = Some("ed448".into());
+    assert!(future_algorithm.requires_newer_client());
+    Ok(())
+}
@@ -1,4 +1,7 @@
+use crate::Result;
+
 #[derive(Clone, Debug, Builder, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Url {
     pub url: String,
     #[serde(
@@ -16,6 +19,22 @@ impl Url {
             url_type: default_url_type(),
         }
     }
+
+    /// Check that this is a well-formed http(s) url
+    pub fn validate_http(&self) -> Result<()> {
+        let rest = match ["https://", "http://"]
+            .iter()
+            .find(|scheme| self.url.starts_with(*scheme))
+        {
+            Some(scheme) => &self.url[scheme.len()..],
+            None => bail!("Not an http(s) url: {}", self.url),
+        };
+        let host = rest.split(|c| c == '/' || c == '?' || c == '#').next();
+        if host.map(str::is_empty).unwrap_or(true) || self.url.contains(char::is_whitespace) {
+            bail!("Malformed url: {}", self.url);
+        }
+        Ok(())
+    }
 }
 pub(crate) fn equals_default_url_type(s: &str) -> bool {
     s == default_url_type()
@@ -0,0 +1,59 @@
+use crate::{
+    id::{check_sealed_version, open_sealed, random_seal_nonce, KdfLimits, PassConfig},
+    util, Result,
+};
+use crev_common::serde::{as_base64, from_base64};
+use miscreant::{self, aead::Algorithm};
+use serde_yaml;
+use std::path::Path;
+
+/// A proof draft encrypted with a passphrase, stored on disk
+///
+/// Uses the same passphrase-based sealing as `LockedId`.
+#[derive(Serialize, Deserialize, Debug)]
+struct EncryptedDraft {
+    version: i64,
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    #[serde(rename = "sealed-draft")]
+    sealed_draft: Vec<u8>,
+    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    #[serde(rename = "seal-nonce")]
+    seal_nonce: Vec<u8>,
+    pass: PassConfig,
+}
+
+pub fn save_encrypted_draft(draft: &str, passphrase: &str, path: &Path) -> Result<()> {
+    let (pass, seal_key) = PassConfig::new_with_key(passphrase)?;
+    let mut siv = miscreant::aead::Aes256Siv::new(&seal_key);
+    let seal_nonce = random_seal_nonce();
+
+    let encrypted = EncryptedDraft {
+        version: crev_data::current_version(),
+        sealed_draft: siv.seal(&seal_nonce, &[], draft.as_bytes()),
+        seal_nonce,
+        pass,
+    };
+
+    util::store_str_to_file(path, &serde_yaml::to_string(&encrypted)?)
+}
+
+/// Load a draft saved with `save_encrypted_draft`
+///
+/// Returns exactly the saved draft, ready for `Content::parse_draft`.
+/// Fails with an `IdError` on eg. a wrong passphrase, like `LockedId::to_unlocked`.
+pub fn load_encrypted_draft(path: &Path, passphrase: &str) -> Result<String> {
+    let encrypted: EncryptedDraft = serde_yaml::from_str(&util::read_file_to_string(path)?)?;
+    check_sealed_version(encrypted.version)?;
+
+    let seal_key = encrypted
+        .pass
+        .derive_key(passphrase, &KdfLimits::default())?;
+    let draft = open_sealed(
+        &seal_key,
+        &encrypted.seal_nonce,
+        &encrypted.sealed_draft,
+        passphrase,
+    )?;
+
+    Ok(std::str::from_utf8(&draft)?.to_owned())
+}
@@ -12,12 +12,102 @@ use std::{
     self, fmt,
     io::{Read, Write},
     path::Path,
+    time::Duration,
 };
 
 use crate::Result;
 use crev_data::id::{OwnId, PubId};
+use zeroize::Zeroizing;
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Maximum KDF parameters accepted when unlocking
+///
+/// `PassConfig` is read from (possibly hostile) files, so its parameters
+/// are checked against these before anything is allocated.
+#[derive(Debug, Clone)]
+pub struct KdfLimits {
+    /// In KiB
+    pub max_memory_size: u32,
+    pub max_iterations: u32,
+}
+
+impl Default for KdfLimits {
+    fn default() -> Self {
+        KdfLimits {
+            max_memory_size: 1024 * 1024,
+            max_iterations: 1024,
+        }
+    }
+}
+
+/// Minimum KDF parameters considered strong enough, see `PassConfig::meets_minimum`
+///
+/// Unlike `KdfLimits`, which protect against hostile parameters, this
+/// is about ids sealed with parameters too weak by today's standards.
+#[derive(Debug, Clone)]
+pub struct KdfPolicy {
+    /// In KiB
+    pub min_memory_size: u32,
+    pub min_iterations: u32,
+}
+
+impl Default for KdfPolicy {
+    /// What `LockedId::from_own_id` currently uses
+    fn default() -> Self {
+        KdfPolicy {
+            min_memory_size: 4096,
+            min_iterations: 192,
+        }
+    }
+}
+
+/// Length of an ed25519 secret key
+const SECRET_KEY_LEN: usize = 32;
+
+/// The sealed secret key decrypted fine, but is not a valid key
+///
+/// Unlike a wrong passphrase, this means the stored id is broken.
+/// Reported as the cause of `IdError::CorruptSecretKey`.
+#[derive(Fail, Debug, Clone, PartialEq, Eq)]
+#[fail(display = "Corrupt secret key: {} bytes instead of 32", len)]
+pub struct CorruptSecretKey {
+    pub len: usize,
+}
+
+/// Why a `LockedId` couldn't be read or unlocked
+///
+/// Returned (inside the error) by `LockedId::to_unlocked` and friends,
+/// so the UI can tell eg. a mistyped passphrase from a broken id file.
+/// Encrypted drafts (see `draft::load_encrypted_draft`) fail the same way.
+#[derive(Fail, Debug, Clone, PartialEq, Eq)]
+pub enum IdError {
+    #[fail(display = "Wrong passphrase")]
+    WrongPassphrase,
+    /// No passphrase was given, and the id needs one
+    #[fail(display = "Passphrase required")]
+    EmptyPassphrase,
+    #[fail(display = "Unsupported id version: {}", _0)]
+    UnsupportedVersion(i64),
+    /// KDF parameters exceed `KdfLimits`, see `PassConfig::check_limits`
+    #[fail(display = "{}", _0)]
+    KdfLimitExceeded(String),
+    /// The stored id is broken, even though the passphrase is right
+    #[fail(display = "Corrupt id: {}", _0)]
+    CorruptId(String),
+    #[fail(display = "Corrupt id: {}", _0)]
+    CorruptSecretKey(#[cause] CorruptSecretKey),
+}
+
+/// Rough cost of the KDF of a `PassConfig`, see `LockedId::kdf_cost_estimate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfCost {
+    /// In KiB
+    pub memory_size: u32,
+    pub iterations: u32,
+    /// Very rough: assumes ~1µs per KiB per iteration
+    pub estimated_time: Duration,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PassConfig {
     version: u32,
     variant: String,
@@ -28,8 +118,223 @@ pub struct PassConfig {
     salt: Vec<u8>,
 }
 
+impl PassConfig {
+    /// Derive a new sealing key from `passphrase`, using a random salt
+    pub(crate) fn new_with_key(passphrase: &str) -> Result<(Self, Zeroizing<Vec<u8>>)> {
+        let mut hasher = Hasher::default();
+        hasher.configure_memory_size(4096);
+
+        Self::new_with_key_using(hasher, passphrase)
+    }
+
+    fn new_with_key_using<'a>(
+        mut hasher: Hasher<'a>,
+        passphrase: &'a str,
+    ) -> Result<(Self, Zeroizing<Vec<u8>>)> {
+        // Always a fresh random salt, so re-sealing never reuses one
+        hasher
+            .with_salt(random_bytes(32))
+            .configure_hash_len(64)
+            .opt_out_of_secret_key(true);
+
+        let pwhash = hasher.with_password(passphrase).hash_raw()?;
+
+        let hasher_config = hasher.config();
+
+        assert_eq!(hasher_config.version(), argonautica::config::Version::_0x13);
+        Ok((
+            PassConfig {
+                salt: pwhash.raw_salt_bytes().to_vec(),
+                iterations: hasher_config.iterations(),
+                memory_size: hasher_config.memory_size(),
+                version: 0x13,
+                variant: hasher_config.variant().as_str().to_string(),
+            },
+            Zeroizing::new(pwhash.raw_hash_bytes().to_vec()),
+        ))
+    }
+
+    /// Fail with `IdError::KdfLimitExceeded` if any parameter exceeds `limits`
+    pub fn check_limits(&self, limits: &KdfLimits) -> Result<()> {
+        if self.memory_size > limits.max_memory_size {
+            return Err(IdError::KdfLimitExceeded(format!(
+                "KDF memory size {} exceeds the limit of {}",
+                self.memory_size, limits.max_memory_size
+            ))
+            .into());
+        }
+        if self.iterations > limits.max_iterations {
+            return Err(IdError::KdfLimitExceeded(format!(
+                "KDF iterations {} exceed the limit of {}",
+                self.iterations, limits.max_iterations
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Are all the parameters at least as strong as `policy` requires
+    ///
+    /// Ids that don't should be re-sealed.
+    pub fn meets_minimum(&self, policy: &KdfPolicy) -> bool {
+        self.memory_size >= policy.min_memory_size && self.iterations >= policy.min_iterations
+    }
+
+    pub fn cost_estimate(&self) -> KdfCost {
+        KdfCost {
+            memory_size: self.memory_size,
+            iterations: self.iterations,
+            estimated_time: Duration::from_micros(
+                u64::from(self.memory_size) * u64::from(self.iterations),
+            ),
+        }
+    }
+
+    /// Human-readable summary, eg. "Argon2id, 4 MiB, 192 iterations, v0x13"
+    pub fn describe(&self) -> String {
+        let mut variant = self.variant.chars();
+        let variant = variant
+            .next()
+            .map(|first| first.to_uppercase().chain(variant).collect())
+            .unwrap_or_else(String::new);
+        let memory = if self.memory_size % 1024 == 0 {
+            format!("{} MiB", self.memory_size / 1024)
+        } else {
+            format!("{} KiB", self.memory_size)
+        };
+        format!(
+            "{}, {}, {} iteration{}, v0x{:x}",
+            variant,
+            memory,
+            self.iterations,
+            if self.iterations == 1 { "" } else { "s" },
+            self.version
+        )
+    }
+
+    /// Derive the sealing key from `passphrase`, using this config
+    pub(crate) fn derive_key(
+        &self,
+        passphrase: &str,
+        limits: &KdfLimits,
+    ) -> Result<Zeroizing<Vec<u8>>> {
+        self.check_limits(limits)?;
+        let mut hasher = Hasher::default();
+
+        hasher
+            .configure_memory_size(self.memory_size)
+            .configure_version(argonautica::config::Version::from_u32(self.version)?)
+            .configure_iterations(self.iterations)
+            .configure_variant(std::str::FromStr::from_str(&self.variant)?)
+            .with_salt(&self.salt)
+            .configure_hash_len(64)
+            .opt_out_of_secret_key(true);
+
+        let pwhash = hasher.with_password(passphrase).hash_raw()?;
+
+        Ok(Zeroizing::new(pwhash.raw_hash_bytes().to_vec()))
+    }
+}
+
+/// Fail with `IdError::UnsupportedVersion` unless this build reads `version`
+pub(crate) fn check_sealed_version(version: i64) -> Result<()> {
+    if !crev_data::supported_versions().contains(&version) {
+        return Err(IdError::UnsupportedVersion(version).into());
+    }
+    Ok(())
+}
+
+/// Open `sealed` with a `seal_key` derived from `passphrase`
+///
+/// Failing to open means the passphrase is wrong (or missing).
+pub(crate) fn open_sealed(
+    seal_key: &[u8],
+    seal_nonce: &[u8],
+    sealed: &[u8],
+    passphrase: &str,
+) -> Result<Zeroizing<Vec<u8>>> {
+    use miscreant::aead::Algorithm;
+
+    let mut siv = miscreant::aead::Aes256Siv::new(seal_key);
+    match siv.open(seal_nonce, &[], sealed) {
+        Ok(opened) => Ok(Zeroizing::new(opened)),
+        Err(_) if passphrase.is_empty() => Err(IdError::EmptyPassphrase.into()),
+        Err(_) => Err(IdError::WrongPassphrase.into()),
+    }
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    rand::thread_rng()
+        .sample_iter(&rand::distributions::Standard)
+        .take(len)
+        .collect()
+}
+
+pub(crate) fn random_seal_nonce() -> Vec<u8> {
+    random_bytes(32)
+}
+
+/// Passphrase of ids sealed with `LockedId::seal_fast`
+#[cfg(any(test, feature = "test-support"))]
+pub const TEST_PASSPHRASE: &str = "test";
+
+/// KDF memory size of ids sealed with `LockedId::seal_fast`, in KiB
+///
+/// The minimum argon2 allows for the lanes the KDF uses (8 KiB per lane),
+/// but at least 256. Lanes default to the number of logical CPUs.
+#[cfg(any(test, feature = "test-support"))]
+pub fn seal_fast_memory_size() -> u32 {
+    std::cmp::max(256, 8 * Hasher::default().config().lanes())
+}
+
+const BEGIN_PUBLIC_CARD: &str = "-----BEGIN CREV ID CARD-----";
+const END_PUBLIC_CARD: &str = "-----END CREV ID CARD-----";
+
+/// Reconstruct the `PubId` from a `LockedId::public_card`
+///
+/// Anything around the card (eg. the rest of a README) is ignored.
+/// Fails if the fingerprint on the card doesn't match its key.
+pub fn parse_public_card(s: &str) -> Result<PubId> {
+    let mut lines = s
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| *line != BEGIN_PUBLIC_CARD);
+    if lines.next().is_none() {
+        bail!("No id card found");
+    }
+
+    let (mut fingerprint, mut url, mut public_key) = (None, None, None);
+    loop {
+        let line = lines
+            .next()
+            .ok_or_else(|| format_err!("Unexpected EOF inside an id card"))?;
+        if line == END_PUBLIC_CARD {
+            break;
+        }
+        let (key, value) = match line.find(':') {
+            Some(i) => (&line[..i], line[i + 1..].trim()),
+            None => bail!("Malformed id card line: {}", line),
+        };
+        match key {
+            "fingerprint" => fingerprint = Some(value.parse::<crev_data::Fingerprint>()?),
+            "url" => url = Some(value.to_owned()),
+            "public-key" => public_key = Some(value),
+            _ => {}
+        }
+    }
+
+    let public_key = public_key.ok_or_else(|| format_err!("Id card without a public key"))?;
+    let pub_id = PubId::from_base64(public_key, url)?;
+    if let Some(fingerprint) = fingerprint {
+        if fingerprint != pub_id.id.fingerprint() {
+            bail!("Id card fingerprint doesn't match its public key");
+        }
+    }
+    Ok(pub_id)
+}
+
 /// Serialized, stored on disk
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LockedId {
     version: i64,
     url: String,
@@ -49,56 +354,102 @@ pub struct LockedId {
 impl fmt::Display for LockedId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // https://github.com/dtolnay/serde-yaml/issues/103
-        f.write_str(&serde_yaml::to_string(self).map_err(|_| fmt::Error)?)
+        f.write_str(&self.to_yaml_string().map_err(|_| fmt::Error)?)
     }
 }
 
 impl LockedId {
     pub fn from_own_id(own_id: &OwnId, passphrase: &str) -> Result<LockedId> {
-        use miscreant::aead::Algorithm;
-        let mut hasher = Hasher::default();
+        let (pass, seal_key) = PassConfig::new_with_key(passphrase)?;
 
-        hasher
-            .configure_memory_size(4096)
-            .configure_hash_len(64)
-            .opt_out_of_secret_key(true);
+        Ok(Self::seal(own_id, pass, &seal_key))
+    }
 
-        let pwhash = hasher.with_password(passphrase).hash_raw()?;
+    /// Like `from_own_id`, but fast: with `TEST_PASSPHRASE` and minimal KDF cost
+    ///
+    /// Only for tests. Never use it for ids used in production.
+    ///
+    /// The memory size (see `seal_fast_memory_size`) depends on the number
+    /// of CPUs, so ids sealed this way might not unlock on a machine
+    /// with more of them.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn seal_fast(own_id: &OwnId) -> Result<LockedId> {
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_memory_size(seal_fast_memory_size())
+            .configure_iterations(1);
+        let (pass, seal_key) = PassConfig::new_with_key_using(hasher, TEST_PASSPHRASE)?;
 
-        let mut siv = miscreant::aead::Aes256Siv::new(pwhash.raw_hash_bytes());
+        Ok(Self::seal(own_id, pass, &seal_key))
+    }
 
-        let seal_nonce: Vec<u8> = rand::thread_rng()
-            .sample_iter(&rand::distributions::Standard)
-            .take(32)
-            .collect();
+    fn seal(own_id: &OwnId, pass: PassConfig, seal_key: &[u8]) -> LockedId {
+        use miscreant::aead::Algorithm;
+        let mut siv = miscreant::aead::Aes256Siv::new(seal_key);
 
-        let hasher_config = hasher.config();
+        let seal_nonce = random_seal_nonce();
 
-        assert_eq!(hasher_config.version(), argonautica::config::Version::_0x13);
-        Ok(LockedId {
+        LockedId {
             version: crev_data::current_version(),
             public_key: own_id.keypair.public.to_bytes().to_vec(),
             sealed_secret_key: siv.seal(&seal_nonce, &[], own_id.keypair.secret.as_bytes()),
             seal_nonce: seal_nonce,
             url: own_id.id.url.clone().unwrap().url,
-            pass: PassConfig {
-                salt: pwhash.raw_salt_bytes().to_vec(),
-                iterations: hasher_config.iterations(),
-                memory_size: hasher_config.memory_size(),
-                version: 0x13,
-                variant: hasher_config.variant().as_str().to_string(),
-            },
-        })
+            pass,
+        }
+    }
+
+    /// How expensive unlocking will be, eg. to warn the user beforehand
+    ///
+    /// Nothing is derived, only the stored parameters are looked at.
+    pub fn kdf_cost_estimate(&self) -> KdfCost {
+        self.pass.cost_estimate()
+    }
+
+    /// Parameters of the KDF deriving the sealing key
+    pub fn pass_config(&self) -> &PassConfig {
+        &self.pass
+    }
+
+    /// A copy of this id moved to `url`, without unlocking it
+    ///
+    /// Possible only because the url is not bound to the sealed secret
+    /// key. If it ever becomes its associated data, changing the url will
+    /// require unlocking and re-sealing the id instead.
+    pub fn with_url(&self, url: String) -> LockedId {
+        LockedId {
+            url,
+            ..self.clone()
+        }
     }
 
     pub fn to_pubid(&self) -> PubId {
         PubId::new(self.public_key.to_owned(), self.url.to_owned())
     }
 
+    /// Is this the id of `other`'s key, no matter the urls
+    pub fn same_key(&self, other: &PubId) -> bool {
+        self.to_pubid().same_key(other)
+    }
+
     pub fn pub_key_as_base64(&self) -> String {
         base64::encode_config(&self.public_key, base64::URL_SAFE)
     }
 
+    /// Public part of the id, formatted for pasting eg. into a README
+    ///
+    /// Only public fields are read. See `parse_public_card`.
+    pub fn public_card(&self) -> String {
+        format!(
+            "{}\nfingerprint: {}\nurl: {}\npublic-key: {}\n{}\n",
+            BEGIN_PUBLIC_CARD,
+            self.to_pubid().id.fingerprint(),
+            self.url,
+            self.pub_key_as_base64(),
+            END_PUBLIC_CARD
+        )
+    }
+
     pub fn save_to(&self, path: &Path) -> Result<()> {
         let mut file = std::fs::OpenOptions::new()
             .create(true)
@@ -114,10 +465,41 @@ impl LockedId {
         let mut content = String::new();
         file.read_to_string(&mut content)?;
 
-        Ok(serde_yaml::from_str::<LockedId>(&content)?)
+        Self::from_yaml_string(&content)
+    }
+
+    /// Serialize to a YAML string, as it would be saved on disk
+    ///
+    /// Together with `from_yaml_string` it allows keeping ids entirely
+    /// in memory, without ever touching a file.
+    pub fn to_yaml_string(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    /// Deserialize from a string written by `to_yaml_string`
+    ///
+    /// Malformed ids fail with `IdError::CorruptId`.
+    pub fn from_yaml_string(s: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str::<LockedId>(s).map_err(|e| IdError::CorruptId(e.to_string()))?)
     }
 
+    /// Unlock with `passphrase`
+    ///
+    /// Failures to unlock are `IdError`s (see `failure::Error::downcast_ref`).
     pub fn to_unlocked(&self, passphrase: &str) -> Result<OwnId> {
+        self.to_unlocked_with_limits(passphrase, &KdfLimits::default())
+    }
+
+    /// Like `to_unlocked`, but get the passphrase from `get_pass` only when needed
+    ///
+    /// The passphrase is zeroed as soon as the id is unlocked.
+    pub fn to_unlocked_with(&self, get_pass: impl FnOnce() -> Zeroizing<String>) -> Result<OwnId> {
+        let passphrase = get_pass();
+        self.to_unlocked(&passphrase)
+    }
+
+    /// Like `to_unlocked`, with custom limits of the KDF parameters
+    pub fn to_unlocked_with_limits(&self, passphrase: &str, limits: &KdfLimits) -> Result<OwnId> {
         let LockedId {
             ref version,
             ref url,
@@ -127,35 +509,43 @@ impl LockedId {
             ref pass,
         } = self;
         {
-            if *version != crev_data::current_version() {
-                bail!("Unsupported version");
-            }
-            use miscreant::aead::Algorithm;
-
-            let mut hasher = Hasher::default();
+            check_sealed_version(*version)?;
 
-            hasher
-                .configure_memory_size(pass.memory_size)
-                .configure_version(argonautica::config::Version::from_u32(pass.version)?)
-                .configure_iterations(pass.iterations)
-                .configure_variant(std::str::FromStr::from_str(&pass.variant)?)
-                .with_salt(&pass.salt)
-                .configure_hash_len(64)
-                .opt_out_of_secret_key(true);
+            let seal_key = pass.derive_key(passphrase, limits)?;
 
-            let pwhash = hasher.with_password(passphrase).hash_raw()?;
-
-            let mut siv = miscreant::aead::Aes256Siv::new(pwhash.raw_hash_bytes());
-
-            let sec_key = siv.open(&seal_nonce, &[], &sealed_secret_key)?;
+            let sec_key = open_sealed(&seal_key, &seal_nonce, &sealed_secret_key, passphrase)?;
+            if sec_key.len() != SECRET_KEY_LEN {
+                return Err(
+                    IdError::CorruptSecretKey(CorruptSecretKey { len: sec_key.len() }).into(),
+                );
+            }
 
-            let res = OwnId::new(url.to_owned(), sec_key)?;
+            let res = OwnId::new(url.to_owned(), &sec_key)
+                .map_err(|e| IdError::CorruptId(e.to_string()))?;
 
             if public_key != &res.keypair.public.to_bytes() {
-                bail!("PubKey mismatch");
+                return Err(IdError::CorruptId("public key mismatch".into()).into());
             }
 
             Ok(res)
         }
     }
+
+    /// Check if `passphrase` unlocks this id
+    ///
+    /// The unlocked secret key is dropped (and zeroed) before returning.
+    /// Fails with an `IdError`, like `to_unlocked`.
+    pub fn check_passphrase(&self, passphrase: &str) -> Result<()> {
+        self.to_unlocked(passphrase)?;
+        Ok(())
+    }
+
+    /// Like `to_unlocked`, but return only the public part of the id
+    ///
+    /// Useful to prove possession of the passphrase, without keeping
+    /// the secret key around longer than necessary.
+    pub fn unlock_public_only(&self, passphrase: &str) -> Result<PubId> {
+        self.check_passphrase(passphrase)?;
+        Ok(self.to_pubid())
+    }
 }
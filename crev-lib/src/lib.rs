@@ -6,6 +6,7 @@ use common_failures::prelude::*;
 #[macro_use]
 extern crate failure;
 
+pub mod draft;
 pub mod id;
 pub mod local;
 pub mod proof;
@@ -14,7 +14,13 @@ use failure::ResultExt;
 use git2;
 use resiter::*;
 use serde_yaml;
-use std::{collections::HashSet, ffi::OsString, fs, io::Write, path::PathBuf};
+use std::{
+    collections::HashSet,
+    ffi::OsString,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UserConfig {
@@ -69,7 +75,37 @@ fn https_to_git_url_test() {
     );
 }
 
+/// Env var overriding the user config directory (`~/.config/crev`)
+pub const CONFIG_DIR_ENV: &str = "CREV_CONFIG_DIR";
+
+pub(crate) fn user_config_root() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os(CONFIG_DIR_ENV) {
+        return Ok(dir.into());
+    }
+    Ok(app_root(AppDataType::UserConfig, &APP_INFO)?)
+}
+
+/// Path of the locked id of the current user
+///
+/// Respects `CONFIG_DIR_ENV`, like everything using `Local`.
+pub fn default_id_path() -> Result<PathBuf> {
+    default_id_path_in(&user_config_root()?)
+}
+
+/// Like `default_id_path`, with the user config directory at `root`
+pub(crate) fn default_id_path_in(root: &Path) -> Result<PathBuf> {
+    let local = Local::new_in(root.to_owned())?;
+    Ok(local.id_path(&local.get_current_userid()?))
+}
+
+/// Load the locked id of the current user, see `default_id_path`
+pub fn load_default_locked_id() -> Result<LockedId> {
+    LockedId::read_from_yaml_file(&default_id_path()?)
+}
+
 /// Local config stored in `~/.config/crev`
+///
+/// The location can be overriden with `CONFIG_DIR_ENV`.
 pub struct Local {
     root_path: PathBuf,
     cache_path: PathBuf,
@@ -77,7 +113,10 @@ pub struct Local {
 
 impl Local {
     fn new() -> Result<Self> {
-        let root_path = app_root(AppDataType::UserConfig, &APP_INFO)?;
+        Self::new_in(user_config_root()?)
+    }
+
+    fn new_in(root_path: PathBuf) -> Result<Self> {
         let cache_path = app_root(AppDataType::UserCache, &APP_INFO)?;
         Ok(Self {
             root_path,
@@ -99,7 +138,12 @@ impl Local {
     }
 
     pub fn auto_create() -> Result<Self> {
-        let repo = Self::new()?;
+        Self::auto_create_in(user_config_root()?)
+    }
+
+    /// Like `auto_create`, with the user config directory at `root_path`
+    pub(crate) fn auto_create_in(root_path: PathBuf) -> Result<Self> {
+        let repo = Self::new_in(root_path)?;
         fs::create_dir_all(&repo.root_path)?;
 
         let config_path = repo.user_config_path();
@@ -1,6 +1,13 @@
+use crate::Result;
 use chrono::prelude::*;
-use crev_data::proof::Content;
-use std::path::PathBuf;
+use crev_data::proof::{Content, Proof};
+use std::{
+    collections::{BTreeMap, HashSet},
+    ffi::OsStr,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 fn type_name(content: &Content) -> &str {
     match content {
@@ -40,3 +47,280 @@ pub(crate) fn rel_store_path(content: &Content) -> PathBuf {
     )
     .with_extension(format!("{}.crev", type_name))
 }
+
+/// Append `proof` to a proof log file at `log_path`
+///
+/// Fails if `proof` is dated before the last proof already in the file,
+/// to catch clock regressions before they end up in a published log.
+pub fn append_proof_checked(log_path: &Path, proof: &Proof) -> Result<()> {
+    if log_path.exists() {
+        if let Some(last) = Proof::parse_from(log_path)?.last() {
+            if proof.content.date() < last.content.date() {
+                bail!(
+                    "Proof date {} is earlier than the last proof in {} ({})",
+                    proof.content.date(),
+                    log_path.display(),
+                    last.content.date()
+                );
+            }
+        }
+    }
+
+    fs::create_dir_all(log_path.parent().expect("Not a root dir"))?;
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .write(true)
+        .open(log_path)?;
+
+    file.write_all(proof.to_string().as_bytes())?;
+    file.write_all(b"\n")?;
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Rewrite the proof file at `path`, passing every proof through `f`
+///
+/// `f` returns the proof to keep (as it is, or eg. re-signed), or `None`
+/// to drop it. The new content is written to a temporary file, checked to
+/// parse back to the same proofs, and only then renamed over the original,
+/// so a crash or a bug never leaves `path` corrupted.
+pub fn rewrite_proof_file(path: &Path, f: impl Fn(Proof) -> Option<Proof>) -> Result<()> {
+    let proofs: Vec<Proof> = Proof::parse_from(path)?.into_iter().filter_map(f).collect();
+
+    let tmp_path = path.with_extension("tmp");
+    let mut file = fs::File::create(&tmp_path)?;
+    for proof in &proofs {
+        file.write_all(proof.to_string().as_bytes())?;
+        file.write_all(b"\n")?;
+    }
+    file.flush()?;
+    file.sync_data()?;
+    drop(file);
+
+    let reparsed = Proof::parse_from(&tmp_path);
+    let matches = match reparsed {
+        Ok(ref reparsed) => {
+            reparsed.len() == proofs.len()
+                && reparsed
+                    .iter()
+                    .zip(proofs.iter())
+                    .all(|(a, b)| a.digest == b.digest)
+        }
+        Err(_) => false,
+    };
+    if !matches {
+        fs::remove_file(&tmp_path)?;
+        bail!(
+            "Rewritten {} does not parse back to the same proofs",
+            path.display()
+        );
+    }
+
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
+
+/// Sign all `contents` with `id` and write them together to `path`
+///
+/// All or nothing: if any of them fails to sign, nothing is written.
+/// Otherwise `path` is replaced (not appended to) with all the proofs at
+/// once, through a temporary file, so it's never left half-written.
+pub fn sign_and_write_batch(
+    contents: &[Content],
+    id: &crev_data::id::OwnId,
+    path: &Path,
+) -> Result<()> {
+    let proofs = contents
+        .iter()
+        .map(|content| content.sign_by(id))
+        .collect::<Result<Vec<_>>>()?;
+
+    crate::util::store_to_file_with(path, |file| {
+        for proof in &proofs {
+            file.write_all(proof.to_string().as_bytes())?;
+            file.write_all(b"\n")?;
+        }
+        Ok(())
+    })
+}
+
+/// Parse every proof file (`*.crev`) under `root`
+///
+/// Each file is parsed on its own, so one bad file doesn't stop
+/// the others from being parsed.
+pub fn parse_dir(root: &Path) -> impl Iterator<Item = (PathBuf, Result<Vec<Proof>>)> {
+    walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_map(|entry| match entry {
+            Err(e) => {
+                let path = e.path().map(Path::to_owned).unwrap_or_default();
+                Some((path, Err(e.into())))
+            }
+            Ok(entry) => {
+                let path = entry.path();
+                if !entry.file_type().is_file() || path.extension() != Some(OsStr::new("crev")) {
+                    return None;
+                }
+                Some((path.to_owned(), Proof::parse_from(path)))
+            }
+        })
+}
+
+/// Remove proofs with the same digest, keeping the first one
+pub fn dedup(proofs: impl IntoIterator<Item = Proof>) -> Vec<Proof> {
+    let mut seen = HashSet::new();
+    proofs
+        .into_iter()
+        .filter(|proof| seen.insert(proof.digest.clone()))
+        .collect()
+}
+
+/// Result of `proof_set_diff`
+#[derive(Debug, Default)]
+pub struct ProofSetDiff {
+    /// Proofs only in the first set
+    pub only_a: Vec<Proof>,
+    /// Proofs only in the second set
+    pub only_b: Vec<Proof>,
+    /// Proofs in both sets (as found in the first one)
+    pub common: Vec<Proof>,
+}
+
+/// Compare two sets of proofs by their digests
+///
+/// Duplicates within a set are reported once.
+pub fn proof_set_diff(a: &[Proof], b: &[Proof]) -> ProofSetDiff {
+    let a_digests: HashSet<&[u8]> = a.iter().map(|proof| proof.digest.as_slice()).collect();
+    let b_digests: HashSet<&[u8]> = b.iter().map(|proof| proof.digest.as_slice()).collect();
+    let (common, only_a): (Vec<Proof>, Vec<Proof>) = a
+        .iter()
+        .cloned()
+        .partition(|proof| b_digests.contains(proof.digest.as_slice()));
+
+    ProofSetDiff {
+        only_a: dedup(only_a),
+        only_b: dedup(
+            b.iter()
+                .filter(|proof| !a_digests.contains(proof.digest.as_slice()))
+                .cloned(),
+        ),
+        common: dedup(common),
+    }
+}
+
+/// Result of `parse_store`
+#[derive(Debug, Default)]
+pub struct StoreParseReport {
+    /// All parsed proofs, without duplicates
+    pub proofs: Vec<Proof>,
+    /// Files that could not be parsed
+    pub errors: Vec<(PathBuf, failure::Error)>,
+    /// Number of `proofs` by kind (see `Content::kind_str`)
+    pub counts: BTreeMap<&'static str, usize>,
+}
+
+/// Parse a whole proof store at `root`, collecting per-file errors
+///
+/// Signatures are not verified.
+pub fn parse_store(root: &Path) -> StoreParseReport {
+    let mut report = StoreParseReport::default();
+    let mut proofs = vec![];
+    for (path, res) in parse_dir(root) {
+        match res {
+            Ok(file_proofs) => proofs.extend(file_proofs),
+            Err(e) => report.errors.push((path, e)),
+        }
+    }
+    report.proofs = dedup(proofs);
+    for proof in &report.proofs {
+        *report.counts.entry(proof.content.kind_str()).or_insert(0) += 1;
+    }
+    report
+}
+
+/// Result of `verify_dir`
+#[derive(Debug, Default)]
+pub struct VerificationReport {
+    /// Proofs with a valid signature
+    pub verified: Vec<Proof>,
+    /// Files that could not be parsed
+    pub file_errors: Vec<(PathBuf, failure::Error)>,
+    /// Proofs that failed to verify, with the files they are in
+    pub proof_errors: Vec<(PathBuf, Proof, failure::Error)>,
+}
+
+/// Parse every proof file under `root` and verify all the proofs in parallel
+///
+/// `progress(done, total)` is called after each proof is verified, from
+/// the worker threads, so the calls can come slightly out of order.
+#[cfg(feature = "parallel")]
+pub fn verify_dir(root: &Path, progress: impl Fn(usize, usize) + Sync) -> VerificationReport {
+    use rayon::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let mut report = VerificationReport::default();
+    let mut proofs = vec![];
+    for (path, res) in parse_dir(root) {
+        match res {
+            Ok(file_proofs) => {
+                proofs.extend(file_proofs.into_iter().map(|proof| (path.clone(), proof)))
+            }
+            Err(e) => report.file_errors.push((path, e)),
+        }
+    }
+
+    let total = proofs.len();
+    let done = AtomicUsize::new(0);
+    let results: Vec<_> = proofs
+        .into_par_iter()
+        .map(|(path, proof)| {
+            let res = proof.verify();
+            progress(done.fetch_add(1, Ordering::SeqCst) + 1, total);
+            (path, proof, res)
+        })
+        .collect();
+
+    for (path, proof, res) in results {
+        match res {
+            Ok(()) => report.verified.push(proof),
+            Err(e) => report.proof_errors.push((path, proof, e)),
+        }
+    }
+    report
+}
+
+fn digest_storage_path(base: &Path, digest: &[u8]) -> PathBuf {
+    let digest = hex::encode(digest);
+    base.join(&digest[0..2])
+        .join(&digest[2..4])
+        .join(format!("{}.crev", digest))
+}
+
+/// Content-addressed path of `proof` under `base`
+///
+/// Sharded by the digest, eg. `ab/cd/abcd....crev`, so identical
+/// proofs always end up in the same file.
+pub fn proof_storage_path(base: &Path, proof: &Proof) -> PathBuf {
+    digest_storage_path(base, &proof.digest)
+}
+
+/// Store `proof` at its `proof_storage_path`, unless it's already there
+pub fn store_proof(base: &Path, proof: &Proof) -> Result<PathBuf> {
+    let path = proof_storage_path(base, proof);
+    if !path.exists() {
+        crate::util::store_str_to_file(&path, &proof.to_string())?;
+    }
+    Ok(path)
+}
+
+/// Load a proof stored with `store_proof`
+pub fn load_proof_by_digest(base: &Path, digest: &[u8]) -> Result<Proof> {
+    let path = digest_storage_path(base, digest);
+    let mut proofs = Proof::parse_from(&path)?;
+    if proofs.len() != 1 || proofs[0].digest != digest {
+        bail!("{} does not contain the expected proof", path.display());
+    }
+    Ok(proofs.remove(0))
+}
@@ -24,3 +24,619 @@ fn lock_and_unlock() -> Result<()> {
     assert_eq!(id.id.id, id_restored.id.id);
     Ok(())
 }
+
+#[test]
+fn locked_id_same_key() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let locked = id::LockedId::seal_fast(&id)?;
+    let moved = crev_data::PubId::from_base64(&locked.pub_key_as_base64(), None)?;
+
+    assert!(locked.same_key(&id.id));
+    assert!(locked.same_key(&moved));
+    assert!(!locked.same_key(&OwnId::generate_for_test().id));
+    Ok(())
+}
+
+#[test]
+fn unlock_public_only() -> Result<()> {
+    let id = OwnId::generate("https://mypage.com/trust.git".into());
+    let locked = id::LockedId::from_own_id(&id, "password")?;
+
+    let pub_id = locked.unlock_public_only("password")?;
+    assert_eq!(pub_id, id.id);
+
+    assert!(locked.unlock_public_only("wrongpassword").is_err());
+    Ok(())
+}
+
+fn trust_proof_dated(id: &OwnId, date: &str) -> Result<crev_data::proof::Proof> {
+    crev_data::proof::TrustBuilder::default()
+        .from(id.id.to_owned())
+        .date(chrono::DateTime::parse_from_rfc3339(date)?)
+        .ids(vec![])
+        .build()
+        .map_err(|e| format_err!("{}", e))?
+        .sign_by(id)
+}
+
+#[test]
+fn append_proof_checked_enforces_date_order() -> Result<()> {
+    let tmp_dir = tempdir::TempDir::new("crev-lib-test")?;
+    let log_path = tmp_dir.path().join("log.crev");
+    let id = OwnId::generate("https://mypage.com/trust.git".into());
+
+    proof::append_proof_checked(&log_path, &trust_proof_dated(&id, "2018-12-01T10:00:00Z")?)?;
+    proof::append_proof_checked(&log_path, &trust_proof_dated(&id, "2018-12-02T10:00:00Z")?)?;
+    assert!(proof::append_proof_checked(
+        &log_path,
+        &trust_proof_dated(&id, "2018-12-01T12:00:00Z")?
+    )
+    .is_err());
+
+    assert_eq!(crev_data::proof::Proof::parse_from(&log_path)?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn rewrite_proof_file_drops_proof() -> Result<()> {
+    let tmp_dir = tempdir::TempDir::new("crev-lib-test")?;
+    let log_path = tmp_dir.path().join("log.crev");
+    let id = OwnId::generate("https://mypage.com/trust.git".into());
+
+    let proofs = vec![
+        trust_proof_dated(&id, "2018-12-01T10:00:00Z")?,
+        trust_proof_dated(&id, "2018-12-02T10:00:00Z")?,
+        trust_proof_dated(&id, "2018-12-03T10:00:00Z")?,
+    ];
+    for proof in &proofs {
+        proof::append_proof_checked(&log_path, proof)?;
+    }
+
+    let dropped = proofs[1].digest.clone();
+    proof::rewrite_proof_file(&log_path, |proof| {
+        if proof.digest == dropped {
+            None
+        } else {
+            Some(proof)
+        }
+    })?;
+
+    let rewritten = crev_data::proof::Proof::parse_from(&log_path)?;
+    assert_eq!(rewritten.len(), 2);
+    assert_eq!(rewritten[0].digest, proofs[0].digest);
+    assert_eq!(rewritten[1].digest, proofs[2].digest);
+    for proof in &rewritten {
+        proof.verify()?;
+    }
+    assert!(!log_path.with_extension("tmp").exists());
+    Ok(())
+}
+
+#[test]
+fn parse_store_reports_errors_and_counts() -> Result<()> {
+    let tmp_dir = tempdir::TempDir::new("crev-lib-test")?;
+    let root = tmp_dir.path();
+    let id = OwnId::generate_for_test();
+
+    let first = trust_proof_dated(&id, "2018-12-01T10:00:00Z")?;
+    let second = trust_proof_dated(&id, "2018-12-02T10:00:00Z")?;
+    proof::append_proof_checked(&root.join("a/trust.crev"), &first)?;
+    proof::append_proof_checked(&root.join("a/trust.crev"), &second)?;
+    // a duplicate in another file
+    proof::append_proof_checked(&root.join("b/copy.crev"), &first)?;
+    util::store_str_to_file(&root.join("b/broken.crev"), "not a proof\n")?;
+    util::store_str_to_file(&root.join("b/README.md"), "not a proof either\n")?;
+
+    let report = proof::parse_store(root);
+    assert_eq!(report.proofs.len(), 2);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].0, root.join("b/broken.crev"));
+    assert_eq!(report.counts.get("trust"), Some(&2));
+    assert_eq!(report.counts.get("code"), None);
+    Ok(())
+}
+
+#[test]
+fn proof_set_diff_overlapping_sets() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let first = trust_proof_dated(&id, "2018-12-01T10:00:00Z")?;
+    let second = trust_proof_dated(&id, "2018-12-02T10:00:00Z")?;
+    let third = trust_proof_dated(&id, "2018-12-03T10:00:00Z")?;
+
+    let a = [first.clone(), second.clone(), first.clone()];
+    let b = [third.clone(), second.clone()];
+    let diff = proof::proof_set_diff(&a, &b);
+    assert_eq!(diff.only_a, vec![first]);
+    assert_eq!(diff.only_b, vec![third]);
+    assert_eq!(diff.common, vec![second]);
+
+    let diff = proof::proof_set_diff(&b, &[]);
+    assert_eq!(diff.only_a.len(), 2);
+    assert!(diff.only_b.is_empty());
+    assert!(diff.common.is_empty());
+    Ok(())
+}
+
+#[test]
+fn load_default_locked_id_from_config_dir() -> Result<()> {
+    let tmp_dir = tempdir::TempDir::new("crev-lib-test")?;
+    let id = OwnId::generate_for_test();
+    let local = Local::auto_create_in(tmp_dir.path().to_owned())?;
+    local.save_locked_id(&id::LockedId::seal_fast(&id)?)?;
+    local.save_current_id(&id.id.id)?;
+
+    let path = local::default_id_path_in(tmp_dir.path())?;
+    assert!(path.starts_with(tmp_dir.path()));
+    let locked = id::LockedId::read_from_yaml_file(&path)?;
+    assert_eq!(locked.to_pubid().id, id.id.id);
+    Ok(())
+}
+
+#[test]
+fn config_dir_env_overrides_user_config_root() -> Result<()> {
+    /// Restores the env var, even if the test panics
+    struct RestoreEnv(Option<std::ffi::OsString>);
+
+    impl Drop for RestoreEnv {
+        fn drop(&mut self) {
+            match self.0.take() {
+                Some(value) => std::env::set_var(local::CONFIG_DIR_ENV, value),
+                None => std::env::remove_var(local::CONFIG_DIR_ENV),
+            }
+        }
+    }
+
+    let tmp_dir = tempdir::TempDir::new("crev-lib-test")?;
+    let _restore = RestoreEnv(std::env::var_os(local::CONFIG_DIR_ENV));
+    std::env::set_var(local::CONFIG_DIR_ENV, tmp_dir.path());
+    assert_eq!(local::user_config_root()?, tmp_dir.path());
+    Ok(())
+}
+
+#[test]
+fn content_addressed_proof_storage() -> Result<()> {
+    let tmp_dir = tempdir::TempDir::new("crev-lib-test")?;
+    let base = tmp_dir.path();
+    let id = OwnId::generate_for_test();
+    let first = trust_proof_dated(&id, "2018-12-01T10:00:00Z")?;
+    let second = trust_proof_dated(&id, "2018-12-02T10:00:00Z")?;
+
+    let path = proof::store_proof(base, &first)?;
+    assert_eq!(path, proof::proof_storage_path(base, &first));
+    assert!(path.starts_with(base.join(&hex::encode(&first.digest)[0..2])));
+    assert_ne!(path, proof::proof_storage_path(base, &second));
+
+    // an identical proof maps to the same path
+    let copy = crev_data::proof::Proof::parse(first.to_string().as_bytes())?.remove(0);
+    assert_eq!(proof::store_proof(base, &copy)?, path);
+
+    let loaded = proof::load_proof_by_digest(base, &first.digest)?;
+    assert_eq!(loaded.digest, first.digest);
+    assert_eq!(loaded.body, first.body);
+    loaded.verify()?;
+    assert!(proof::load_proof_by_digest(base, &second.digest).is_err());
+    Ok(())
+}
+
+#[test]
+fn encrypted_draft_round_trip() -> Result<()> {
+    let tmp_dir = tempdir::TempDir::new("crev-lib-test")?;
+    let path = tmp_dir.path().join("draft.yaml");
+    let text = "comment: |\n  secret notes\n";
+
+    draft::save_encrypted_draft(text, "password", &path)?;
+    assert!(!util::read_file_to_string(&path)?.contains("secret notes"));
+    assert_eq!(draft::load_encrypted_draft(&path, "password")?, text);
+    let err = draft::load_encrypted_draft(&path, "wrongpassword").unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<id::IdError>(),
+        Some(&id::IdError::WrongPassphrase)
+    );
+    let err = draft::load_encrypted_draft(&path, "").unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<id::IdError>(),
+        Some(&id::IdError::EmptyPassphrase)
+    );
+    Ok(())
+}
+
+#[test]
+fn seal_fast_unlocks_with_test_passphrase() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let locked = id::LockedId::seal_fast(&id)?;
+
+    assert_eq!(locked.to_unlocked(id::TEST_PASSPHRASE)?.id, id.id);
+    assert!(locked.to_unlocked("wrongpassword").is_err());
+    Ok(())
+}
+
+#[test]
+fn unlock_with_passphrase_callback() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let locked = id::LockedId::seal_fast(&id)?;
+
+    let unlocked =
+        locked.to_unlocked_with(|| zeroize::Zeroizing::new(id::TEST_PASSPHRASE.into()))?;
+    assert_eq!(unlocked.id, id.id);
+    assert!(locked
+        .to_unlocked_with(|| zeroize::Zeroizing::new("wrong".into()))
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn unlock_supported_older_version() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let current = serde_yaml::to_string(&id::LockedId::seal_fast(&id)?)?;
+    let current_version_line = format!("version: {}", crev_data::current_version());
+    assert!(current.contains(&current_version_line));
+
+    let older = current.replace(&current_version_line, "version: -99999");
+    let older: id::LockedId = serde_yaml::from_str(&older)?;
+    assert_eq!(older.to_unlocked(id::TEST_PASSPHRASE)?.id, id.id);
+
+    let newer = current.replace(&current_version_line, "version: 1000");
+    let newer: id::LockedId = serde_yaml::from_str(&newer)?;
+    assert!(newer.to_unlocked(id::TEST_PASSPHRASE).is_err());
+    Ok(())
+}
+
+#[test]
+fn kdf_cost_estimate_matches_config() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let fast = id::LockedId::seal_fast(&id)?.kdf_cost_estimate();
+    assert_eq!(fast.memory_size, id::seal_fast_memory_size());
+    assert_eq!(fast.iterations, 1);
+
+    let locked = id::LockedId::from_own_id(&id, "password")?;
+    let cost = locked.kdf_cost_estimate();
+    let stored = serde_yaml::to_string(&locked)?;
+    assert!(stored.contains(&format!("memory-size: {}", cost.memory_size)));
+    assert!(stored.contains(&format!("iterations: {}", cost.iterations)));
+    assert!(cost.estimated_time > fast.estimated_time);
+    Ok(())
+}
+
+#[test]
+fn unlock_rejects_hostile_kdf_params() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let locked = serde_yaml::to_string(&id::LockedId::seal_fast(&id)?)?;
+    // `seal_fast` uses the minimal memory size
+    let fast_memory_size = format!("memory-size: {}", id::seal_fast_memory_size());
+    assert!(locked.contains(&fast_memory_size));
+
+    let hostile = locked.replace(&fast_memory_size, "memory-size: 4294967295");
+    let hostile: id::LockedId = serde_yaml::from_str(&hostile)?;
+    let err = hostile.to_unlocked(id::TEST_PASSPHRASE).unwrap_err();
+    assert!(err.to_string().contains("memory size"));
+
+    let strict = id::KdfLimits {
+        max_memory_size: 128,
+        ..Default::default()
+    };
+    let locked: id::LockedId = serde_yaml::from_str(&locked)?;
+    assert!(locked
+        .to_unlocked_with_limits(id::TEST_PASSPHRASE, &strict)
+        .is_err());
+    locked.to_unlocked(id::TEST_PASSPHRASE)?;
+    Ok(())
+}
+
+#[test]
+fn unlock_reports_corrupt_secret_key() -> Result<()> {
+    use miscreant::aead::Algorithm;
+
+    let id = OwnId::generate_for_test();
+    let mut locked = serde_yaml::to_value(&id::LockedId::seal_fast(&id)?)?;
+    let pass: id::PassConfig = serde_yaml::from_value(locked["pass"].clone())?;
+    let seal_key = pass.derive_key(id::TEST_PASSPHRASE, &id::KdfLimits::default())?;
+    let seal_nonce = base64::decode_config(
+        locked["seal-nonce"]
+            .as_str()
+            .expect("seal-nonce is a string"),
+        base64::URL_SAFE,
+    )?;
+    // validly sealed, but one byte too short
+    let sealed = miscreant::aead::Aes256Siv::new(&seal_key).seal(&seal_nonce, &[], &[0u8; 31]);
+    locked
+        .as_mapping_mut()
+        .expect("locked id is a mapping")
+        .insert(
+            "sealed-secret-key".into(),
+            base64::encode_config(&sealed, base64::URL_SAFE).into(),
+        );
+    let locked: id::LockedId = serde_yaml::from_value(locked)?;
+
+    let err = locked.to_unlocked(id::TEST_PASSPHRASE).unwrap_err();
+    assert_eq!(
+        err.find_root_cause()
+            .downcast_ref::<id::CorruptSecretKey>()
+            .map(|e| e.len),
+        Some(31)
+    );
+    assert_eq!(
+        err.downcast_ref::<id::IdError>(),
+        Some(&id::IdError::CorruptSecretKey(id::CorruptSecretKey {
+            len: 31
+        }))
+    );
+    // a wrong passphrase is told apart
+    let err = locked.to_unlocked("wrong").unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<id::IdError>(),
+        Some(&id::IdError::WrongPassphrase)
+    );
+    Ok(())
+}
+
+#[test]
+fn reseal_uses_fresh_salt_and_nonce() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let seal = || -> Result<serde_yaml::Value> {
+        let locked = id::LockedId::from_own_id(&id, "password")?;
+        Ok(serde_yaml::to_value(&locked)?)
+    };
+
+    let first = seal()?;
+    let second = seal()?;
+    assert_ne!(first["pass"]["salt"], second["pass"]["salt"]);
+    assert_ne!(first["seal-nonce"], second["seal-nonce"]);
+    Ok(())
+}
+
+#[test]
+fn locked_id_yaml_string_round_trip() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let locked = id::LockedId::seal_fast(&id)?;
+
+    let s = locked.to_yaml_string()?;
+    assert_eq!(s, locked.to_string());
+    let restored = id::LockedId::from_yaml_string(&s)?;
+    assert_eq!(restored.to_unlocked(id::TEST_PASSPHRASE)?.id, id.id);
+    assert!(id::LockedId::from_yaml_string("not: an id").is_err());
+    Ok(())
+}
+
+#[test]
+fn kdf_policy_flags_weak_configs() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let policy = id::KdfPolicy::default();
+
+    let weak = id::LockedId::seal_fast(&id)?;
+    assert!(!weak.pass_config().meets_minimum(&policy));
+
+    let strong = id::LockedId::from_own_id(&id, "password")?;
+    assert!(strong.pass_config().meets_minimum(&policy));
+
+    let stricter = id::KdfPolicy {
+        min_iterations: strong.kdf_cost_estimate().iterations + 1,
+        ..Default::default()
+    };
+    assert!(!strong.pass_config().meets_minimum(&stricter));
+    Ok(())
+}
+
+#[test]
+fn locked_id_with_url_still_unlocks() -> Result<()> {
+    let id = OwnId::generate("https://old.example.com/proofs.git".into());
+    let locked = id::LockedId::seal_fast(&id)?;
+
+    let moved = locked.with_url("https://new.example.com/proofs.git".into());
+    let moved = id::LockedId::from_yaml_string(&moved.to_yaml_string()?)?;
+    assert_eq!(
+        moved.to_pubid().url.unwrap().url,
+        "https://new.example.com/proofs.git"
+    );
+    assert!(moved.same_key(&id.id));
+
+    let unlocked = moved.to_unlocked(id::TEST_PASSPHRASE)?;
+    assert_eq!(unlocked.id.id, id.id.id);
+    assert_eq!(
+        unlocked.id.url.unwrap().url,
+        "https://new.example.com/proofs.git"
+    );
+    Ok(())
+}
+
+#[test]
+fn unlock_failures_are_id_errors() -> Result<()> {
+    let id = OwnId::generate_for_test();
+    let locked = id::LockedId::seal_fast(&id)?;
+    let unlock_err = |locked: &id::LockedId, passphrase: &str| -> Option<id::IdError> {
+        locked
+            .check_passphrase(passphrase)
+            .unwrap_err()
+            .downcast_ref::<id::IdError>()
+            .cloned()
+    };
+
+    assert_eq!(
+        unlock_err(&locked, "wrong"),
+        Some(id::IdError::WrongPassphrase)
+    );
+    assert_eq!(unlock_err(&locked, ""), Some(id::IdError::EmptyPassphrase));
+
+    let yaml = locked.to_yaml_string()?;
+    let future = id::LockedId::from_yaml_string(&yaml.replace(
+        &format!("version: {}", crev_data::current_version()),
+        "version: 7",
+    ))?;
+    assert_eq!(
+        unlock_err(&future, id::TEST_PASSPHRASE),
+        Some(id::IdError::UnsupportedVersion(7))
+    );
+
+    let other_key = id::LockedId::seal_fast(&OwnId::generate_for_test())?;
+    let mut mismatched = serde_yaml::to_value(&locked)?;
+    mismatched
+        .as_mapping_mut()
+        .expect("locked id is a mapping")
+        .insert("public-key".into(), other_key.pub_key_as_base64().into());
+    let mismatched: id::LockedId = serde_yaml::from_value(mismatched)?;
+    match unlock_err(&mismatched, id::TEST_PASSPHRASE) {
+        Some(id::IdError::CorruptId(_)) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    let strict = id::KdfLimits {
+        max_memory_size: 128,
+        ..Default::default()
+    };
+    match locked
+        .to_unlocked_with_limits(id::TEST_PASSPHRASE, &strict)
+        .unwrap_err()
+        .downcast_ref::<id::IdError>()
+    {
+        Some(id::IdError::KdfLimitExceeded(_)) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    match id::LockedId::from_yaml_string("not: an id")
+        .unwrap_err()
+        .downcast_ref::<id::IdError>()
+    {
+        Some(id::IdError::CorruptId(_)) => {}
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    locked.check_passphrase(id::TEST_PASSPHRASE)?;
+    Ok(())
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn verify_dir_reports_progress_and_errors() -> Result<()> {
+    use std::sync::Mutex;
+
+    let tmp_dir = tempdir::TempDir::new("crev-lib-test")?;
+    let root = tmp_dir.path();
+    let id = OwnId::generate_for_test();
+
+    let first = trust_proof_dated(&id, "2018-12-01T10:00:00Z")?;
+    let second = trust_proof_dated(&id, "2018-12-02T10:00:00Z")?;
+    proof::append_proof_checked(&root.join("a/trust.crev"), &first)?;
+    proof::append_proof_checked(&root.join("a/trust.crev"), &second)?;
+    let tampered = second.to_string().replace("2018-12-02", "2018-12-03");
+    util::store_str_to_file(&root.join("b/tampered.crev"), &tampered)?;
+    util::store_str_to_file(&root.join("b/broken.crev"), "not a proof\n")?;
+
+    let calls = Mutex::new(vec![]);
+    let report = proof::verify_dir(root, |done, total| {
+        calls.lock().unwrap().push((done, total));
+    });
+
+    let mut calls = calls.into_inner().unwrap();
+    calls.sort();
+    assert_eq!(calls, vec![(1, 3), (2, 3), (3, 3)]);
+    assert_eq!(report.verified.len(), 2);
+    assert_eq!(report.file_errors.len(), 1);
+    assert_eq!(report.file_errors[0].0, root.join("b/broken.crev"));
+    assert_eq!(report.proof_errors.len(), 1);
+    assert_eq!(report.proof_errors[0].0, root.join("b/tampered.crev"));
+    Ok(())
+}
+
+#[test]
+fn pass_config_describe() -> Result<()> {
+    let pass: id::PassConfig = serde_yaml::from_str(
+        "version: 19\nvariant: argon2id\niterations: 3\nmemory-size: 4096\nsalt: AAAA\n",
+    )?;
+    assert_eq!(pass.describe(), "Argon2id, 4 MiB, 3 iterations, v0x13");
+
+    let weak = id::LockedId::seal_fast(&OwnId::generate_for_test())?;
+    let memory = id::seal_fast_memory_size();
+    let memory = if memory % 1024 == 0 {
+        format!("{} MiB", memory / 1024)
+    } else {
+        format!("{} KiB", memory)
+    };
+    assert_eq!(
+        weak.pass_config().describe(),
+        format!("Argon2id, {}, 1 iteration, v0x13", memory)
+    );
+    Ok(())
+}
+
+#[test]
+fn public_card_round_trip() -> Result<()> {
+    let id = OwnId::generate("https://example.com/proofs.git".into());
+    let locked = id::LockedId::seal_fast(&id)?;
+
+    let card = locked.public_card();
+    assert!(card.contains(&id.id.id.fingerprint().to_string()));
+    let readme = format!("# About me\n\nMy crev id:\n\n{}\nThanks!\n", card);
+    let pub_id = id::parse_public_card(&readme)?;
+    assert_eq!(pub_id, id.id);
+    assert_eq!(
+        pub_id.url.map(|url| url.url),
+        Some("https://example.com/proofs.git".to_owned())
+    );
+
+    let other = id::LockedId::seal_fast(&OwnId::generate_for_test())?;
+    let forged = card.replace(&locked.pub_key_as_base64(), &other.pub_key_as_base64());
+    assert!(id::parse_public_card(&forged).is_err());
+    assert!(id::parse_public_card(&card[..card.len() / 2]).is_err());
+    assert!(id::parse_public_card("no card here").is_err());
+    Ok(())
+}
+
+#[test]
+fn sign_and_write_batch_is_all_or_nothing() -> Result<()> {
+    let tmp_dir = tempdir::TempDir::new("crev-lib-test")?;
+    let path = tmp_dir.path().join("batch.crev");
+    let id = OwnId::generate_for_test();
+    let trust = |comment: String| -> Result<crev_data::proof::Content> {
+        Ok(crev_data::proof::TrustBuilder::default()
+            .from(id.id.to_owned())
+            .ids(vec![OwnId::generate_for_test().id])
+            .comment(comment)
+            .build()
+            .map_err(|e| format_err!("{}", e))?
+            .into())
+    };
+    util::store_str_to_file(&path, "previous content\n")?;
+
+    // the one in the middle is too long to be signed
+    let failing = vec![
+        trust("first".into())?,
+        trust("x".repeat(20_000))?,
+        trust("third".into())?,
+    ];
+    assert!(proof::sign_and_write_batch(&failing, &id, &path).is_err());
+    assert_eq!(util::read_file_to_string(&path)?, "previous content\n");
+
+    let contents = vec![trust("first".into())?, trust("second".into())?];
+    proof::sign_and_write_batch(&contents, &id, &path)?;
+    let written = crev_data::proof::Proof::parse_from(&path)?;
+    assert_eq!(written.len(), 2);
+    for (proof, content) in written.iter().zip(&contents) {
+        proof.verify()?;
+        assert_eq!(proof.body, content.to_string());
+    }
+    Ok(())
+}
+
+#[test]
+fn verify_signature_only_of_unknown_content() -> Result<()> {
+    use crev_data::proof::{self, ProofType, Serialized};
+
+    let id = OwnId::generate_for_test();
+    let other = OwnId::generate_for_test();
+    let version = crev_data::current_version();
+    let body = format!(
+        "version: {}\nfrom-future: {}\nsomething-new: [1, 2, 3]\n",
+        version, id.id.id
+    );
+    let signature = id.sign(&proof::signed_bytes(&body, version));
+    let serialized = Serialized {
+        body,
+        signature: base64::encode_config(&signature, base64::URL_SAFE),
+        type_: ProofType::Trust,
+    };
+
+    let parsed = Serialized::parse(serialized.to_string().as_bytes())?.remove(0);
+    assert!(parsed.to_parsed().is_err());
+    parsed.verify_signature_only(&id.id)?;
+    assert!(parsed.verify_signature_only(&other.id).is_err());
+    Ok(())
+}
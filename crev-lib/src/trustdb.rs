@@ -17,7 +17,7 @@ struct TrustInfo {
 impl<'a> From<&'a proof::Trust> for TrustInfo {
     fn from(trust: &proof::Trust) -> Self {
         TrustInfo {
-            trust: trust.trust,
+            trust: trust.trust.clone(),
             date: trust.date().with_timezone(&Utc),
         }
     }
@@ -145,7 +145,7 @@ impl TrustDB {
         let from = &trust.from;
         self.record_url_from_from_field(&trust.date_utc(), &from);
         for to in &trust.ids {
-            self.add_trust_raw(&from.id, &to.id, trust.date_utc(), trust.trust);
+            self.add_trust_raw(&from.id, &to.id, trust.date_utc(), trust.trust.clone());
         }
         if self.trusted_ids.contains(&from.id) {
             for to in &trust.ids {
@@ -283,7 +283,10 @@ impl TrustDB {
 
     fn get_ids_trusted_by(&self, id: &Id) -> impl Iterator<Item = (Level, &Id)> {
         if let Some(map) = self.trust_id_to_id.get(id) {
-            Some(map.iter().map(|(id, trust_info)| (trust_info.trust, id)))
+            Some(
+                map.iter()
+                    .map(|(id, trust_info)| (trust_info.trust.clone(), id)),
+            )
         } else {
             None
         }
@@ -366,7 +369,7 @@ pub struct TrustDistanceParams {
 impl TrustDistanceParams {
     fn distance_by_level(&self, level: Level) -> Option<u64> {
         Some(match level {
-            Level::None => return None,
+            Level::Unknown(_) | Level::None => return None,
             Level::Low => self.low_trust_distance,
             Level::Medium => self.medium_trust_distance,
             Level::High => self.high_trust_distance,